@@ -0,0 +1,335 @@
+use std::{
+    collections::VecDeque,
+    io,
+    process::{Command, Stdio},
+    thread,
+    time::Duration
+};
+use crate::{state::Panel, sys_poller::Snapshot};
+
+// Oldest entries are dropped from the alert log past this
+const MAX_LOG_ENTRIES: usize = 1000;
+
+// What a rule watches
+#[derive(Clone, Debug, PartialEq)]
+pub enum Metric {
+    // Usage averaged over all cores, in %
+    Cpu,
+    // Used RAM and swap, in % of the total
+    Memory,
+    Swap,
+    // Space left on the file system mounted there, in GB
+    DiskAvailable(String),
+    // Number of processes with that name
+    ProcessCount(String)
+}
+
+impl Metric {
+    // None when the snapshot has nothing to compare, e.g. a disk that is not mounted
+    // or a recording, which holds no processes
+    pub fn value(&self, snapshot: &Snapshot) -> Option<f64> {
+        let percent_used = |(_, used, total): (u64, u64, u64)| {
+            (total > 0).then(|| used as f64 / total as f64 * 100.0)
+        };
+        match self {
+            Metric::Cpu => Some(snapshot.avg_cpu_usage),
+            Metric::Memory => percent_used(snapshot.memory),
+            Metric::Swap => percent_used(snapshot.swap),
+            Metric::DiskAvailable(mount_point) => snapshot.disks.iter()
+                .find(|d| d.mount_point.to_string_lossy() == mount_point.as_str())
+                .map(|d| d.available_space as f64 / 1_000_000_000.0),
+            Metric::ProcessCount(name) => (!snapshot.processes.is_empty())
+                .then(|| snapshot.processes.iter().filter(|p| p.name == *name).count() as f64)
+        }
+    }
+
+    pub fn format_value(&self, value: f64) -> String {
+        match self {
+            Metric::Cpu | Metric::Memory | Metric::Swap => format!("{value:.1}%"),
+            Metric::DiskAvailable(_) => format!("{value:.1} GB"),
+            Metric::ProcessCount(_) if value == 0.0 => "not running".to_string(),
+            Metric::ProcessCount(_) => format!("{value} running")
+        }
+    }
+
+    // The block whose border flashes while the rule fires
+    pub fn panel(&self) -> Panel {
+        match self {
+            Metric::Cpu => Panel::Cpu,
+            Metric::Memory | Metric::Swap => Panel::Memory,
+            Metric::DiskAvailable(_) => Panel::Disks,
+            // The process table is drawn in the graph pane
+            Metric::ProcessCount(_) => Panel::Graph
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Above(f64),
+    Below(f64)
+}
+
+impl Comparison {
+    fn holds(self, value: f64) -> bool {
+        match self {
+            Comparison::Above(threshold) => value > threshold,
+            Comparison::Below(threshold) => value < threshold
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    // How long the condition has to hold before the rule fires
+    pub duration: Duration,
+    // Run through the shell every time the rule starts firing
+    pub command: Option<String>
+}
+
+impl AlertRule {
+    // "cpu > 90% for 10s", used when the config gives the rule no name
+    pub fn describe(&self) -> String {
+        let (operator, threshold) = match self.comparison {
+            Comparison::Above(threshold) => (">", threshold),
+            Comparison::Below(threshold) => ("<", threshold)
+        };
+        let condition = match &self.metric {
+            Metric::Cpu => format!("cpu {operator} {threshold}%"),
+            Metric::Memory => format!("memory used {operator} {threshold}%"),
+            Metric::Swap => format!("swap used {operator} {threshold}%"),
+            Metric::DiskAvailable(mount_point) => format!("disk available on {mount_point} {operator} {threshold} GB"),
+            Metric::ProcessCount(name) if self.comparison == Comparison::Below(1.0) => format!("{name} not running"),
+            Metric::ProcessCount(name) => format!("{name} processes {operator} {threshold}")
+        };
+        if self.duration.is_zero() {
+            condition
+        } else {
+            format!("{condition} for {}s", self.duration.as_secs())
+        }
+    }
+}
+
+pub struct AlertEvent {
+    pub timestamp_ms: u64,
+    pub rule: String,
+    // Started firing, or stopped
+    pub firing: bool,
+    pub value: String
+}
+
+struct RuleState {
+    rule: AlertRule,
+    // Timestamp of the first snapshot in a row that met the condition
+    breached_since_ms: Option<u64>,
+    firing: bool
+}
+
+// Checks every rule against each snapshot. Time is taken from the snapshots rather than
+// the clock, so "for 10s" means 10 s of samples whether they are live or replayed
+pub struct Alerts {
+    rules: Vec<RuleState>,
+    // Oldest first
+    pub log: VecDeque<AlertEvent>,
+    // Entries scrolled past, the log is shown newest first
+    pub log_offset: usize,
+    // Latest snapshot evaluated so far. Seeking back in a replay feeds earlier ones again,
+    // they bring the rules up to date without being logged twice
+    evaluated_until_ms: Option<u64>,
+    run_commands: bool
+}
+
+impl Alerts {
+    pub fn new(rules: Vec<AlertRule>, run_commands: bool) -> Self {
+        Self {
+            rules: rules.into_iter()
+                .map(|rule| RuleState { rule, breached_since_ms: None, firing: false })
+                .collect(),
+            log: VecDeque::new(),
+            log_offset: 0,
+            evaluated_until_ms: None,
+            run_commands
+        }
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn firing(&self) -> impl Iterator<Item = &AlertRule> {
+        self.rules.iter().filter(|state| state.firing).map(|state| &state.rule)
+    }
+
+    pub fn is_firing(&self, panel: Panel) -> bool {
+        self.firing().any(|rule| rule.metric.panel() == panel)
+    }
+
+    // Returns a message for the status line when a rule started firing,
+    // or when its command could not be started
+    pub fn evaluate(&mut self, snapshot: &Snapshot) -> Option<String> {
+        let mut status = None;
+        let mut events = Vec::new();
+        let seen = self.evaluated_until_ms.is_some_and(|until_ms| snapshot.timestamp_ms <= until_ms);
+        for state in &mut self.rules {
+            let Some(value) = state.rule.metric.value(snapshot) else {
+                continue;
+            };
+
+            let firing = if state.rule.comparison.holds(value) {
+                let since_ms = *state.breached_since_ms.get_or_insert(snapshot.timestamp_ms);
+                snapshot.timestamp_ms.saturating_sub(since_ms) >= state.rule.duration.as_millis() as u64
+            } else {
+                state.breached_since_ms = None;
+                false
+            };
+            if firing == state.firing {
+                continue;
+            }
+            state.firing = firing;
+            if seen {
+                continue;
+            }
+
+            let event = AlertEvent {
+                timestamp_ms: snapshot.timestamp_ms,
+                rule: state.rule.name.clone(),
+                firing,
+                value: state.rule.metric.format_value(value)
+            };
+            if firing {
+                status = Some(format!("Alert: {} ({})", event.rule, event.value));
+                if let (Some(command), true) = (&state.rule.command, self.run_commands) {
+                    if let Err(e) = run_command(command, &event) {
+                        status = Some(format!("Alert {}: could not run its command: {e}", event.rule));
+                    }
+                }
+            }
+            events.push(event);
+        }
+        for event in events {
+            self.push_log(event);
+        }
+        self.evaluated_until_ms = self.evaluated_until_ms.max(Some(snapshot.timestamp_ms));
+        status
+    }
+
+    // Forget how long conditions have held, e.g. after seeking in a replay.
+    // The log is kept, and snapshots evaluated before are not logged again
+    pub fn reset(&mut self) {
+        for state in &mut self.rules {
+            state.breached_since_ms = None;
+            state.firing = false;
+        }
+    }
+
+    fn push_log(&mut self, event: AlertEvent) {
+        if self.log.len() >= MAX_LOG_ENTRIES {
+            self.log.pop_front();
+        }
+        self.log.push_back(event);
+        // Keep the entries the user scrolled to in view
+        if self.log_offset > 0 {
+            self.log_offset = (self.log_offset + 1).min(self.log.len() - 1);
+        }
+    }
+
+    // The last entry always stays visible
+    pub fn scroll_log(&mut self, delta: isize) {
+        let max_offset = self.log.len().saturating_sub(1);
+        self.log_offset = self.log_offset.saturating_add_signed(delta).min(max_offset);
+    }
+}
+
+// The command runs in the background with the rule's name and value in its environment.
+// Its output would end up all over the screen, so it goes nowhere
+fn run_command(command: &str, event: &AlertEvent) -> io::Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let mut child = shell
+        .arg(command)
+        .env("SYS_TUI_ALERT", &event.rule)
+        .env("SYS_TUI_VALUE", &event.value)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // Reap it once it exits so it doesn't linger as a zombie
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::fixtures::{alert_rule as rule, disk, process};
+
+    fn snapshot(timestamp_ms: u64, cpu_usage: f64, processes: &[&str]) -> Snapshot {
+        Snapshot {
+            timestamp_ms,
+            avg_cpu_usage: cpu_usage,
+            memory: (1000, 9000, 10_000),
+            disks: vec![disk("sda1", "/", 100_000_000_000, 4_000_000_000, 0, 0)],
+            processes: processes.iter().enumerate().map(|(pid, name)| process(pid as u32, name, 0.0, 0)).collect(),
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn rules_fire_once_the_condition_held_long_enough() {
+        let mut alerts = Alerts::new(vec![rule(Metric::Cpu, Comparison::Above(90.0), 10)], false);
+        assert_eq!(alerts.rules[0].rule.name, "cpu > 90% for 10s");
+
+        assert_eq!(alerts.evaluate(&snapshot(0, 95.0, &[])), None);
+        // Dipping below the threshold starts the count over
+        alerts.evaluate(&snapshot(5_000, 50.0, &[]));
+        alerts.evaluate(&snapshot(10_000, 95.0, &[]));
+        assert!(!alerts.is_firing(Panel::Cpu));
+
+        let status = alerts.evaluate(&snapshot(20_000, 97.5, &[]));
+        assert_eq!(status.as_deref(), Some("Alert: cpu > 90% for 10s (97.5%)"));
+        assert!(alerts.is_firing(Panel::Cpu));
+        // Still firing, nothing new is logged
+        alerts.evaluate(&snapshot(21_000, 99.0, &[]));
+        alerts.evaluate(&snapshot(22_000, 20.0, &[]));
+
+        assert!(!alerts.is_firing(Panel::Cpu));
+        let log: Vec<(u64, bool, &str)> = alerts.log.iter()
+            .map(|event| (event.timestamp_ms, event.firing, event.value.as_str()))
+            .collect();
+        assert_eq!(log, vec![(20_000, true, "97.5%"), (22_000, false, "20.0%")]);
+    }
+
+    #[test]
+    fn memory_disk_and_process_rules() {
+        let mut alerts = Alerts::new(vec![
+            rule(Metric::Memory, Comparison::Above(85.0), 0),
+            rule(Metric::DiskAvailable("/".to_string()), Comparison::Below(5.0), 0),
+            rule(Metric::DiskAvailable("/data".to_string()), Comparison::Below(5.0), 0),
+            rule(Metric::ProcessCount("nginx".to_string()), Comparison::Below(1.0), 0)
+        ], false);
+
+        alerts.evaluate(&snapshot(0, 0.0, &["init", "nginx"]));
+        let firing: Vec<String> = alerts.firing().map(|rule| rule.name.clone()).collect();
+        assert_eq!(firing, vec!["memory used > 85%", "disk available on / < 5 GB"]);
+        assert!(alerts.is_firing(Panel::Memory) && alerts.is_firing(Panel::Disks));
+
+        alerts.evaluate(&snapshot(1000, 0.0, &["init"]));
+        assert!(alerts.is_firing(Panel::Graph));
+        assert_eq!(alerts.log.back().unwrap().value, "not running");
+
+        // Recordings have no processes, the process rule is left as it was
+        alerts.evaluate(&snapshot(2000, 0.0, &[]));
+        assert!(alerts.is_firing(Panel::Graph));
+        assert_eq!(alerts.log.len(), 3);
+    }
+}
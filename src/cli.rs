@@ -0,0 +1,81 @@
+use clap::{Args, Parser, Subcommand};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+use crate::settings::{Settings, MIN_SAMPLE_INTERVAL, MAX_SAMPLE_INTERVAL, MIN_HISTORY, MAX_HISTORY};
+
+#[derive(Parser)]
+#[command(version, about = "Monitor CPU, memory, disks, network and processes from the terminal")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file to use instead of $XDG_CONFIG_HOME/sys_tui/config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Time between two samples, in milliseconds [default: 500]
+    #[arg(long, global = true, value_name = "MS", value_parser = clap::value_parser!(u64).range(
+        MIN_SAMPLE_INTERVAL.as_millis() as u64..=MAX_SAMPLE_INTERVAL.as_millis() as u64
+    ))]
+    pub interval: Option<u64>,
+
+    /// How much history the graphs show, in seconds [default: 25]
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(
+        MIN_HISTORY.as_secs()..=MAX_HISTORY.as_secs()
+    ))]
+    pub history: Option<u64>,
+
+    /// Append every sample to FILE as JSON lines, for later analysis or replay
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print samples as JSON instead of starting the interface
+    Snapshot(SnapshotArgs),
+    /// Serve metrics over HTTP in the Prometheus text format instead of starting the interface
+    Serve(ServeArgs),
+    /// Play back a file written with --record in the interface
+    Replay(ReplayArgs)
+}
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    /// Number of samples to print, one JSON object per line, --interval apart
+    #[arg(long, short = 'n', value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub count: u64,
+
+    /// How many processes to include, busiest first
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub top: usize,
+
+    /// Indent the JSON output
+    #[arg(long)]
+    pub pretty: bool
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on, metrics are served on /metrics
+    #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:9184")]
+    pub listen: SocketAddr
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Recording to play back
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf
+}
+
+impl Cli {
+    // Flags that were given override the config file and the defaults
+    pub fn override_settings(&self, settings: &mut Settings) {
+        if let Some(interval) = self.interval {
+            settings.sample_interval = Duration::from_millis(interval);
+        }
+        if let Some(history) = self.history {
+            settings.history = Duration::from_secs(history);
+        }
+    }
+}
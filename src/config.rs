@@ -0,0 +1,439 @@
+use std::{collections::HashMap, env, fmt, fs, io, path::{Path, PathBuf}, time::Duration};
+use ratatui::style::Color;
+use serde::Deserialize;
+use crate::{
+    alerts::{AlertRule, Comparison, Metric},
+    keymap::{Action, KeyBinding, KeyMap},
+    settings::{Settings, MIN_SAMPLE_INTERVAL, MAX_SAMPLE_INTERVAL, MIN_HISTORY, MAX_HISTORY},
+    state::Graph,
+    theme::{Theme, PRESET_NAMES}
+};
+
+// Layout of config.toml. Every key is optional and unknown keys are rejected
+// so that typos don't go unnoticed
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    interval_ms: Option<u64>,
+    history_secs: Option<u64>,
+    default_graph: Option<Graph>,
+    graph_size_percentage: Option<u16>,
+    // Where CSV exports are written
+    export_dir: Option<PathBuf>,
+    #[serde(default)]
+    theme: ThemeFile,
+    #[serde(default)]
+    keys: HashMap<Action, KeyBinding>,
+    #[serde(default)]
+    alerts: Vec<AlertFile>
+}
+
+// Starts from a preset ("dark" unless given) and overrides single colors.
+// Colors are names ("lightcyan") or hex values ("#1e1e2e")
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    preset: Option<String>,
+    background: Option<String>,
+    foreground: Option<String>,
+    border: Option<String>,
+    graph_border: Option<String>,
+    accent: Option<String>,
+    highlight: Option<String>,
+    text_accent: Option<String>,
+    ok: Option<String>,
+    warning: Option<String>,
+    critical: Option<String>,
+    muted: Option<String>,
+    series: Option<Vec<String>>
+}
+
+// One [[alerts]] table, e.g.
+//   metric = "cpu", above = 90, for_secs = 10
+//   metric = "disk_available", mount_point = "/", below = 5
+//   metric = "process", process = "nginx"
+// Thresholds are in % for cpu, memory and swap, in GB for disk_available, and a process
+// count for process, which fires when the process is not running unless told otherwise
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AlertFile {
+    name: Option<String>,
+    metric: AlertMetric,
+    above: Option<f64>,
+    below: Option<f64>,
+    for_secs: Option<u64>,
+    mount_point: Option<String>,
+    process: Option<String>,
+    // Shell command run when the rule starts firing
+    command: Option<String>
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AlertMetric {
+    Cpu,
+    Memory,
+    Swap,
+    DiskAvailable,
+    Process
+}
+
+// Everything the app takes from the config file, with defaults filled in
+pub struct Config {
+    pub settings: Settings,
+    pub default_graph: Graph,
+    pub graph_size_percentage: u16,
+    pub export_dir: PathBuf,
+    pub theme: Theme,
+    pub keymap: KeyMap,
+    pub alerts: Vec<AlertRule>
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            settings: Settings::default(),
+            default_graph: Graph::CPU,
+            graph_size_percentage: 60,
+            // The directory the app was started from
+            export_dir: PathBuf::from("."),
+            theme: Theme::default(),
+            keymap: KeyMap::default(),
+            alerts: Vec::new()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, source: toml::de::Error },
+    Invalid { path: PathBuf, message: String }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read { path, source } => write!(f, "could not read config file {}: {source}", path.display()),
+            ConfigError::Parse { path, source } => write!(f, "invalid config file {}:\n{source}", path.display()),
+            ConfigError::Invalid { path, message } => write!(f, "invalid config file {}: {message}", path.display())
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// $XDG_CONFIG_HOME/sys_tui/config.toml, falling back to ~/.config as the XDG spec says
+pub fn default_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("sys_tui").join("config.toml"))
+}
+
+impl Config {
+    // A path given explicitly must exist. The default file is optional
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default())
+            }
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if !required && e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => return Err(ConfigError::Read { path, source })
+        };
+        let file: ConfigFile = match toml::from_str(&contents) {
+            Ok(file) => file,
+            Err(source) => return Err(ConfigError::Parse { path, source })
+        };
+        Self::from_file(file).map_err(|message| ConfigError::Invalid { path, message })
+    }
+
+    fn from_file(file: ConfigFile) -> Result<Self, String> {
+        let mut config = Self::default();
+
+        if let Some(interval) = file.interval_ms {
+            let interval = Duration::from_millis(interval);
+            if !(MIN_SAMPLE_INTERVAL..=MAX_SAMPLE_INTERVAL).contains(&interval) {
+                return Err(format!(
+                    "interval_ms must be between {} and {}",
+                    MIN_SAMPLE_INTERVAL.as_millis(), MAX_SAMPLE_INTERVAL.as_millis()
+                ));
+            }
+            config.settings.sample_interval = interval;
+        }
+        if let Some(history) = file.history_secs {
+            let history = Duration::from_secs(history);
+            if !(MIN_HISTORY..=MAX_HISTORY).contains(&history) {
+                return Err(format!(
+                    "history_secs must be between {} and {}",
+                    MIN_HISTORY.as_secs(), MAX_HISTORY.as_secs()
+                ));
+            }
+            config.settings.history = history;
+        }
+        if let Some(graph) = file.default_graph {
+            config.default_graph = graph;
+        }
+        if let Some(percentage) = file.graph_size_percentage {
+            if percentage > 100 {
+                return Err("graph_size_percentage must be between 0 and 100".to_string());
+            }
+            config.graph_size_percentage = percentage;
+        }
+        if let Some(dir) = file.export_dir {
+            config.export_dir = dir;
+        }
+
+        config.theme = theme_from_file(file.theme)?;
+        config.keymap = KeyMap::with_overrides(&file.keys).map_err(|e| format!("[keys]: {e}"))?;
+        config.alerts = file.alerts.into_iter()
+            .enumerate()
+            .map(|(i, alert)| alert_from_file(alert).map_err(|e| format!("alerts[{i}]: {e}")))
+            .collect::<Result<_, _>>()?;
+        Ok(config)
+    }
+}
+
+fn theme_from_file(file: ThemeFile) -> Result<Theme, String> {
+    let mut theme = match &file.preset {
+        Some(name) => Theme::preset(name).ok_or_else(|| format!(
+            "theme.preset: unknown theme \"{name}\", expected one of {}", PRESET_NAMES.join(", ")
+        ))?,
+        None => Theme::default()
+    };
+
+    let colors = [
+        ("background", file.background, &mut theme.background),
+        ("foreground", file.foreground, &mut theme.foreground),
+        ("border", file.border, &mut theme.border),
+        ("graph_border", file.graph_border, &mut theme.graph_border),
+        ("accent", file.accent, &mut theme.accent),
+        ("highlight", file.highlight, &mut theme.highlight),
+        ("text_accent", file.text_accent, &mut theme.text_accent),
+        ("ok", file.ok, &mut theme.ok),
+        ("warning", file.warning, &mut theme.warning),
+        ("critical", file.critical, &mut theme.critical),
+        ("muted", file.muted, &mut theme.muted)
+    ];
+    let mut customized = false;
+    for (key, value, color) in colors {
+        if let Some(value) = value {
+            *color = parse_color(&format!("theme.{key}"), &value)?;
+            customized = true;
+        }
+    }
+    if let Some(series) = file.series {
+        if series.is_empty() {
+            return Err("theme.series must have at least one color".to_string());
+        }
+        theme.series = series.iter()
+            .map(|value| parse_color("theme.series", value))
+            .collect::<Result<_, _>>()?;
+        customized = true;
+    }
+
+    // A modified preset is cycled through as its own theme, next to the unmodified one
+    if customized {
+        theme.name = "custom".to_string();
+    }
+    Ok(theme)
+}
+
+fn alert_from_file(file: AlertFile) -> Result<AlertRule, String> {
+    if file.mount_point.is_some() && file.metric != AlertMetric::DiskAvailable {
+        return Err("mount_point only applies to the disk_available metric".to_string());
+    }
+    if file.process.is_some() && file.metric != AlertMetric::Process {
+        return Err("process only applies to the process metric".to_string());
+    }
+
+    let metric = match file.metric {
+        AlertMetric::Cpu => Metric::Cpu,
+        AlertMetric::Memory => Metric::Memory,
+        AlertMetric::Swap => Metric::Swap,
+        AlertMetric::DiskAvailable => Metric::DiskAvailable(
+            file.mount_point.ok_or("the disk_available metric needs a mount_point")?
+        ),
+        AlertMetric::Process => Metric::ProcessCount(
+            file.process.ok_or("the process metric needs the name of a process")?
+        )
+    };
+    let comparison = match (file.above, file.below, &metric) {
+        (Some(threshold), None, _) => Comparison::Above(threshold),
+        (None, Some(threshold), _) => Comparison::Below(threshold),
+        (None, None, Metric::ProcessCount(_)) => Comparison::Below(1.0),
+        (Some(_), Some(_), _) => return Err("set either above or below, not both".to_string()),
+        (None, None, _) => return Err("set a threshold with above or below".to_string())
+    };
+
+    let mut rule = AlertRule {
+        name: String::new(),
+        metric,
+        comparison,
+        duration: Duration::from_secs(file.for_secs.unwrap_or(0)),
+        command: file.command
+    };
+    rule.name = file.name.unwrap_or_else(|| rule.describe());
+    Ok(rule)
+}
+
+fn parse_color(key: &str, value: &str) -> Result<Color, String> {
+    value.parse().map_err(|_| format!("{key}: \"{value}\" is not a color name or #rrggbb value"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use super::*;
+    use crate::keymap::KeyChord;
+
+    fn config(toml: &str) -> Result<Config, String> {
+        let file: ConfigFile = toml::from_str(toml).map_err(|e| e.to_string())?;
+        Config::from_file(file)
+    }
+
+    fn alerts(toml: &str) -> Result<Vec<AlertRule>, String> {
+        config(toml).map(|config| config.alerts)
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for (toml, key) in [
+            ("interval_msec = 500", "interval_msec"),
+            ("[theme]\ncolour = \"red\"", "colour"),
+            ("[[alerts]]\nmetric = \"cpu\"\nthreshold = 90", "threshold")
+        ] {
+            let error = config(toml).err().unwrap();
+            assert!(error.contains(&format!("unknown field `{key}`")), "{error}");
+        }
+        assert!(config("[keys]\nfly = \"f\"").err().unwrap().contains("unknown variant `fly`"));
+    }
+
+    #[test]
+    fn settings_are_checked() {
+        let config = config("interval_ms = 500\nhistory_secs = 120\ndefault_graph = \"memory\"").unwrap();
+        assert_eq!(config.settings.sample_interval, Duration::from_millis(500));
+        assert_eq!(config.settings.history, Duration::from_secs(120));
+        assert!(matches!(config.default_graph, Graph::MEMORY));
+
+        assert!(self::config("interval_ms = 1").err().unwrap().starts_with("interval_ms must be between"));
+        assert_eq!(
+            self::config("graph_size_percentage = 101").err().as_deref(),
+            Some("graph_size_percentage must be between 0 and 100")
+        );
+    }
+
+    #[test]
+    fn theme_colors_are_read_from_the_config() {
+        let theme = config("[theme]\nbackground = \"#1e1e2e\"\nborder = \"lightcyan\"").unwrap().theme;
+        assert_eq!(theme.background, Color::Rgb(0x1e, 0x1e, 0x2e));
+        assert_eq!(theme.border, Color::LightCyan);
+        assert_eq!(theme.graph_border, Theme::default().graph_border);
+
+        assert_eq!(
+            config("[theme]\nborder = \"#12345\"").err().as_deref(),
+            Some("theme.border: \"#12345\" is not a color name or #rrggbb value")
+        );
+    }
+
+    #[test]
+    fn themes_are_looked_up_by_name() {
+        let theme = config("[theme]\npreset = \"solarized\"").unwrap().theme;
+        assert_eq!(theme.name, "solarized");
+        assert_eq!(theme.accent, Theme::preset("solarized").unwrap().accent);
+
+        // Changing a color makes it a theme of its own
+        let theme = config("[theme]\npreset = \"light\"\naccent = \"#ff8000\"").unwrap().theme;
+        assert_eq!(theme.name, "custom");
+        assert_eq!(theme.accent, Color::Rgb(255, 128, 0));
+        assert_eq!(theme.background, Theme::preset("light").unwrap().background);
+
+        assert_eq!(
+            config("[theme]\npreset = \"neon\"").err().as_deref(),
+            Some("theme.preset: unknown theme \"neon\", expected one of dark, light, solarized, high-contrast, monochrome")
+        );
+    }
+
+    #[test]
+    fn key_bindings_can_be_overridden() {
+        let keymap = config(r#"
+            [keys]
+            quit = "x"
+            show_cpu_graph = ["c", "F1"]
+        "#).unwrap().keymap;
+
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(keymap.action(&key(KeyCode::Char('x'))), Some(Action::Quit));
+        // An override replaces every default chord of the action
+        assert_eq!(keymap.action(&key(KeyCode::Char('q'))), None);
+        assert_eq!(keymap.action(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), None);
+        assert_eq!(keymap.action(&key(KeyCode::F(1))), Some(Action::ShowCpuGraph));
+        assert_eq!(keymap.action(&key(KeyCode::Char('c'))), Some(Action::ShowCpuGraph));
+        // The others keep their defaults
+        assert_eq!(keymap.action(&key(KeyCode::Char('m'))), Some(Action::ShowMemoryGraph));
+
+        assert_eq!(
+            config("[keys]\nquit = \"m\"").err().as_deref(),
+            Some("[keys]: \"m\" is bound to both show_memory_graph and quit")
+        );
+        assert_eq!(
+            config("[keys]\nshow_cpu_graph = \"x\"\nquit = \"x\"\nshow_memory_graph = \"x\"").err().as_deref(),
+            Some("[keys]: \"x\" is bound to both quit and show_cpu_graph")
+        );
+    }
+
+    #[test]
+    fn invalid_key_chords_are_rejected() {
+        assert_eq!(KeyChord::parse("ctrl+c"), Ok(KeyChord::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+        assert_eq!(KeyChord::parse("shift+k"), Ok(KeyChord::new(KeyCode::Char('K'), KeyModifiers::NONE)));
+        for chord in ["", "ctrl+", "hyper+x", "pagedn", "f13", "ctrl+shift+"] {
+            assert!(KeyChord::parse(chord).is_err(), "{chord:?} was accepted");
+        }
+
+        let error = config("[keys]\nquit = \"ctrl+esc\"").err().unwrap();
+        assert!(error.starts_with("[keys]: unknown key \"ctrl+esc\""), "{error}");
+    }
+
+    #[test]
+    fn alert_rules_are_read_from_the_config() {
+        let rules = alerts(r#"
+            [[alerts]]
+            metric = "cpu"
+            above = 90
+            for_secs = 10
+            command = "notify-send busy"
+
+            [[alerts]]
+            name = "root almost full"
+            metric = "disk_available"
+            mount_point = "/"
+            below = 5
+
+            [[alerts]]
+            metric = "process"
+            process = "nginx"
+        "#).unwrap();
+
+        let names: Vec<&str> = rules.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, vec!["cpu > 90% for 10s", "root almost full", "nginx not running"]);
+        assert_eq!(rules[0].command.as_deref(), Some("notify-send busy"));
+        assert_eq!(rules[1].metric, Metric::DiskAvailable("/".to_string()));
+        assert_eq!(rules[2].comparison, Comparison::Below(1.0));
+
+        assert_eq!(
+            alerts("[[alerts]]\nmetric = \"memory\"").unwrap_err(),
+            "alerts[0]: set a threshold with above or below"
+        );
+        assert_eq!(
+            alerts("[[alerts]]\nmetric = \"cpu\"\nabove = 1\n[[alerts]]\nmetric = \"disk_available\"\nbelow = 1").unwrap_err(),
+            "alerts[1]: the disk_available metric needs a mount_point"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::{Path, PathBuf}
+};
+use crate::state::TimeSeries;
+
+// Lays the series out side by side, one row per sample time and one column per series.
+// Series that have no sample at a given time, e.g. rates on the first sample, leave the cell empty
+pub fn to_csv(start_timestamp_ms: u64, columns: &[(String, &TimeSeries)]) -> String {
+    let mut rows: BTreeMap<u64, Vec<Option<f64>>> = BTreeMap::new();
+    for (i, (_, series)) in columns.iter().enumerate() {
+        for (elapsed_ms, value) in series.iter() {
+            rows.entry(*elapsed_ms as u64).or_insert_with(|| vec![None; columns.len()])[i] = Some(*value);
+        }
+    }
+
+    let mut csv = String::from("timestamp_ms,elapsed_ms");
+    for (name, _) in columns {
+        csv.push(',');
+        csv.push_str(&escape_field(name));
+    }
+    csv.push('\n');
+
+    for (elapsed_ms, values) in rows {
+        csv.push_str(&format!("{},{elapsed_ms}", start_timestamp_ms + elapsed_ms));
+        for value in values {
+            csv.push(',');
+            if let Some(value) = value {
+                csv.push_str(&value.to_string());
+            }
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+// Quote names holding a comma or a quote, mount points and interface names can hold anything
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Writes sys_tui-<UTC date>-<UTC time>.csv in the directory, with a counter appended
+// when an export was already made within the same second
+pub fn write_csv(dir: &Path, timestamp_ms: u64, contents: &str) -> io::Result<PathBuf> {
+    let stem = format!("sys_tui-{}", format_file_timestamp(timestamp_ms));
+    let mut path = dir.join(format!("{stem}.csv"));
+    let mut copy = 1;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())?;
+                return Ok(path);
+            },
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                copy += 1;
+                path = dir.join(format!("{stem}-{copy}.csv"));
+            },
+            Err(e) => return Err(e)
+        }
+    }
+}
+
+// 20231114-221320 for 2023-11-14 22:13:20 UTC
+fn format_file_timestamp(timestamp_ms: u64) -> String {
+    let seconds = timestamp_ms / 1000;
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!("{year:04}{month:02}{day:02}-{:02}{:02}{:02}", time / 3600, time / 60 % 60, time % 60)
+}
+
+// Days since 1970-01-01 to a (year, month, day) date in the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::*;
+
+    #[test]
+    fn series_are_joined_on_time() {
+        let mut usage = TimeSeries::new();
+        let mut rate = TimeSeries::new();
+        usage.push(0.0, 25_000.0, 10.0);
+        usage.push(500.0, 25_000.0, 12.5);
+        rate.push(500.0, 25_000.0, 2048.0);
+
+        let csv = to_csv(1_700_000_000_000, &[("cpu usage %".to_string(), &usage), ("eth0, rx B/s".to_string(), &rate)]);
+        assert_eq!(
+            csv,
+            "timestamp_ms,elapsed_ms,cpu usage %,\"eth0, rx B/s\"\n\
+             1700000000000,0,10,\n\
+             1700000000500,500,12.5,2048\n"
+        );
+    }
+
+    #[test]
+    fn files_are_named_after_the_time_and_never_overwritten() {
+        assert_eq!(format_file_timestamp(1_700_000_000_000), "20231114-221320");
+        assert_eq!(format_file_timestamp(951_782_400_000), "20000229-000000");
+
+        let dir = std::env::temp_dir().join(format!("sys_tui_export_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let first = write_csv(&dir, 1_700_000_000_000, "a\n").unwrap();
+        let second = write_csv(&dir, 1_700_000_000_400, "b\n").unwrap();
+        assert_eq!(first.file_name().unwrap(), "sys_tui-20231114-221320.csv");
+        assert_eq!(second.file_name().unwrap(), "sys_tui-20231114-221320-2.csv");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "b\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
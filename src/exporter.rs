@@ -0,0 +1,251 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{mpsc::RecvTimeoutError, Arc, Mutex},
+    thread,
+    time::Duration
+};
+use crate::{
+    metrics::MetricsSource,
+    sampler::{Sampler, SamplerMessage},
+    settings::Settings,
+    sys_poller::{DiskData, NetworkData, Snapshot}
+};
+
+// A client that doesn't finish sending its request within this time is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const FIRST_SAMPLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// (name, type, help, value) of the metrics reported once per disk and once per interface
+type DiskMetric = (&'static str, &'static str, &'static str, fn(&DiskData) -> u64);
+type NetworkMetric = (&'static str, &'static str, &'static str, fn(&NetworkData) -> u64);
+
+// `sys_tui serve`: the sampler thread keeps running like it does for the UI, and every scrape
+// of /metrics renders the latest snapshot in the Prometheus text exposition format.
+// Requests are handled one at a time, which is plenty for a scraper or two.
+// Only returns when the sampler thread stops, serving stale metrics from then on would hide it
+pub fn serve<S: MetricsSource + Send + 'static>(source: S, settings: &Settings, listen: SocketAddr) -> io::Result<()> {
+    let listener = TcpListener::bind(listen)?;
+    let sampler = Sampler::spawn(source, settings.sample_interval);
+
+    let first = match sampler.recv_timeout(FIRST_SAMPLE_TIMEOUT) {
+        Ok(SamplerMessage::Snapshot(snapshot)) => *snapshot,
+        _ => return Err(io::Error::new(io::ErrorKind::TimedOut, "the sampler did not produce a first sample"))
+    };
+    let latest = Arc::new(Mutex::new(first));
+    eprintln!("sys_tui: serving metrics on http://{}/metrics", listener.local_addr()?);
+
+    let server_latest = Arc::clone(&latest);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let metrics = render(&server_latest.lock().unwrap());
+            // A single misbehaving client must not take the exporter down
+            let result = stream.and_then(|stream| handle_connection(stream, &metrics));
+            if let Err(e) = result {
+                eprintln!("sys_tui: {e}");
+            }
+        }
+    });
+
+    // Keep only the newest sample around, however long it takes until the next scrape
+    loop {
+        match sampler.recv_timeout(FIRST_SAMPLE_TIMEOUT) {
+            Ok(SamplerMessage::Snapshot(snapshot)) => *latest.lock().unwrap() = *snapshot,
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => return Err(io::Error::other("the sampler thread stopped"))
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, metrics: &str) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers are not needed for anything, but have to be read before answering
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", CONTENT_TYPE, metrics.to_string()),
+        (Some("GET"), Some("/")) => ("200 OK", "text/plain", "sys_tui exporter, metrics are on /metrics\n".to_string()),
+        (Some("GET"), Some(_)) => ("404 Not Found", "text/plain", "not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain", "only GET is supported\n".to_string())
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+// Metric names follow the MetricsSource accessors, e.g. get_avg_cpu_usage becomes
+// sys_tui_avg_cpu_usage_percent. Disk and network sizes are in bytes, the base unit Prometheus
+// expects, but memory and swap are only sampled in whole megabytes and are labelled as such
+pub fn render(snapshot: &Snapshot) -> String {
+    let mut out = Metrics::default();
+
+    out.family("sys_tui_cpu_info", "gauge", "CPU brand, the value is always 1");
+    out.sample("sys_tui_cpu_info", &[("brand", snapshot.cpu_brand.as_deref().unwrap_or(""))], 1.0);
+
+    if let Some(core_count) = snapshot.core_count {
+        out.family("sys_tui_core_count", "gauge", "Number of physical cores");
+        out.sample("sys_tui_core_count", &[], core_count as f64);
+    }
+
+    out.family("sys_tui_avg_cpu_usage_percent", "gauge", "CPU usage averaged over all cores");
+    out.sample("sys_tui_avg_cpu_usage_percent", &[], snapshot.avg_cpu_usage);
+
+    out.family("sys_tui_per_core_usage_percent", "gauge", "CPU usage of every core");
+    for (core, usage) in snapshot.per_core_usage.iter().enumerate() {
+        out.sample("sys_tui_per_core_usage_percent", &[("core", &core.to_string())], *usage);
+    }
+
+    if let Some(frequency) = snapshot.avg_cpu_frequency {
+        out.family("sys_tui_avg_cpu_frequency_mhz", "gauge", "CPU frequency averaged over all cores");
+        out.sample("sys_tui_avg_cpu_frequency_mhz", &[], frequency as f64);
+    }
+
+    out.family("sys_tui_per_core_frequency_mhz", "gauge", "Frequency of every core");
+    for (core, frequency) in snapshot.per_core_frequency.iter().enumerate() {
+        out.sample("sys_tui_per_core_frequency_mhz", &[("core", &core.to_string())], *frequency as f64);
+    }
+
+    for (kind, (free, used, total)) in [("memory", snapshot.memory), ("swap", snapshot.swap)] {
+        for (field, mb) in [("free", free), ("used", used), ("total", total)] {
+            let name = format!("sys_tui_{kind}_{field}_megabytes");
+            out.family(&name, "gauge", &format!("Amount of {field} {kind}"));
+            out.sample(&name, &[], mb as f64);
+        }
+    }
+
+    let disk_metrics: [DiskMetric; 4] = [
+        ("sys_tui_disk_total_space_bytes", "gauge", "Size of the file system", |d| d.total_space),
+        ("sys_tui_disk_available_space_bytes", "gauge", "Space left on the file system", |d| d.available_space),
+        ("sys_tui_disk_read_bytes_total", "counter", "Bytes read from the device since boot", |d| d.read_bytes),
+        ("sys_tui_disk_written_bytes_total", "counter", "Bytes written to the device since boot", |d| d.written_bytes)
+    ];
+    for (name, kind, help, value) in disk_metrics {
+        out.family(name, kind, help);
+        for d in &snapshot.disks {
+            let (disk, mount_point) = (d.name.to_string_lossy(), d.mount_point.to_string_lossy());
+            out.sample(name, &[("disk", &disk), ("mount_point", &mount_point)], value(d) as f64);
+        }
+    }
+
+    let network_metrics: [NetworkMetric; 6] = [
+        ("sys_tui_network_received_bytes_total", "counter", "Bytes received", |n| n.total_received),
+        ("sys_tui_network_transmitted_bytes_total", "counter", "Bytes transmitted", |n| n.total_transmitted),
+        ("sys_tui_network_packets_received_total", "counter", "Packets received", |n| n.total_packets_received),
+        ("sys_tui_network_packets_transmitted_total", "counter", "Packets transmitted", |n| n.total_packets_transmitted),
+        ("sys_tui_network_errors_on_received_total", "counter", "Errors while receiving", |n| n.total_errors_on_received),
+        ("sys_tui_network_errors_on_transmitted_total", "counter", "Errors while transmitting", |n| n.total_errors_on_transmitted)
+    ];
+    for (name, kind, help, value) in network_metrics {
+        out.family(name, kind, help);
+        for n in &snapshot.networks {
+            out.sample(name, &[("interface", &n.name)], value(n) as f64);
+        }
+    }
+
+    out.family("sys_tui_processes_count", "gauge", "Number of running processes");
+    out.sample("sys_tui_processes_count", &[], snapshot.processes.len() as f64);
+
+    out.text
+}
+
+// Builds the exposition text, one HELP/TYPE header per family followed by its samples
+#[derive(Default)]
+struct Metrics {
+    text: String
+}
+
+impl Metrics {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {name} {help}");
+        let _ = writeln!(self.text, "# TYPE {name} {kind}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels.iter()
+                .map(|(label, value)| format!("{label}=\"{}\"", escape_label(value)))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {value}");
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use super::*;
+    use crate::metrics::fixtures::disk;
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            cpu_brand: Some("Test \"CPU\"".to_string()),
+            core_count: Some(2),
+            avg_cpu_usage: 37.5,
+            per_core_usage: vec![25.0, 50.0],
+            memory: (6000, 2000, 8000),
+            disks: vec![disk("sda1", "/", 1000, 250, 4096, 512)],
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn samples_are_rendered_with_labels() {
+        let text = render(&snapshot());
+        assert!(text.contains("# TYPE sys_tui_avg_cpu_usage_percent gauge\nsys_tui_avg_cpu_usage_percent 37.5\n"));
+        assert!(text.contains("sys_tui_per_core_usage_percent{core=\"1\"} 50\n"));
+        assert!(text.contains("sys_tui_memory_used_megabytes 2000\n"));
+        assert!(text.contains("sys_tui_disk_available_space_bytes{disk=\"sda1\",mount_point=\"/\"} 250\n"));
+        assert!(text.contains("sys_tui_disk_read_bytes_total{disk=\"sda1\",mount_point=\"/\"} 4096\n"));
+        assert!(text.contains("sys_tui_cpu_info{brand=\"Test \\\"CPU\\\"\"} 1\n"));
+        // Frequencies were never reported, so there is no sample for them
+        assert!(!text.contains("sys_tui_avg_cpu_frequency_mhz"));
+    }
+
+    fn request(request: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            handle_connection(stream, &render(&snapshot())).unwrap();
+        });
+
+        let mut client = TcpStream::connect(address).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+        response
+    }
+
+    #[test]
+    fn metrics_are_served_over_http() {
+        let response = request("GET /metrics HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(&format!("Content-Type: {CONTENT_TYPE}\r\n")));
+        assert!(response.ends_with("sys_tui_processes_count 0\n"));
+
+        assert!(request("GET /other HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404 Not Found"));
+        assert!(request("POST /metrics HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405 Method Not Allowed"));
+    }
+}
@@ -0,0 +1,419 @@
+use std::{collections::HashMap, fmt};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+// Everything a key can be bound to. Names in the config file are the snake_case variants
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    ToggleHelp,
+    ShowCpuGraph,
+    TogglePerCore,
+    ToggleFrequency,
+    ShowMemoryGraph,
+    ShowDiskGraph,
+    ShowNetworkGraph,
+    ShowProcesses,
+    ShowAlerts,
+    NextDisk,
+    ExpandGraph,
+    ReduceGraph,
+    IncreaseInterval,
+    DecreaseInterval,
+    IncreaseHistory,
+    DecreaseHistory,
+    CycleTheme,
+    ExportCsv,
+    // Process view only
+    SelectUp,
+    SelectDown,
+    PageUp,
+    PageDown,
+    SelectFirst,
+    SelectLast,
+    PreviousSortColumn,
+    NextSortColumn,
+    ReverseSort,
+    Filter,
+    SendSignal,
+    // Replay only
+    TogglePlayback,
+    SlowerPlayback,
+    FasterPlayback,
+    SeekBackward,
+    SeekForward,
+    JumpTo
+}
+
+impl Action {
+    // Order of the help panel
+    pub const ALL: [Action; 36] = [
+        Action::Quit,
+        Action::ToggleHelp,
+        Action::ShowCpuGraph,
+        Action::TogglePerCore,
+        Action::ToggleFrequency,
+        Action::ShowMemoryGraph,
+        Action::ShowDiskGraph,
+        Action::NextDisk,
+        Action::ShowNetworkGraph,
+        Action::ShowProcesses,
+        Action::ShowAlerts,
+        Action::ExpandGraph,
+        Action::ReduceGraph,
+        Action::IncreaseInterval,
+        Action::DecreaseInterval,
+        Action::IncreaseHistory,
+        Action::DecreaseHistory,
+        Action::CycleTheme,
+        Action::ExportCsv,
+        Action::SelectUp,
+        Action::SelectDown,
+        Action::PageUp,
+        Action::PageDown,
+        Action::SelectFirst,
+        Action::SelectLast,
+        Action::PreviousSortColumn,
+        Action::NextSortColumn,
+        Action::ReverseSort,
+        Action::Filter,
+        Action::SendSignal,
+        Action::TogglePlayback,
+        Action::SlowerPlayback,
+        Action::FasterPlayback,
+        Action::SeekBackward,
+        Action::SeekForward,
+        Action::JumpTo
+    ];
+
+    // Name used in the [keys] table of the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ToggleHelp => "toggle_help",
+            Action::ShowCpuGraph => "show_cpu_graph",
+            Action::TogglePerCore => "toggle_per_core",
+            Action::ToggleFrequency => "toggle_frequency",
+            Action::ShowMemoryGraph => "show_memory_graph",
+            Action::ShowDiskGraph => "show_disk_graph",
+            Action::ShowNetworkGraph => "show_network_graph",
+            Action::ShowProcesses => "show_processes",
+            Action::ShowAlerts => "show_alerts",
+            Action::NextDisk => "next_disk",
+            Action::ExpandGraph => "expand_graph",
+            Action::ReduceGraph => "reduce_graph",
+            Action::IncreaseInterval => "increase_interval",
+            Action::DecreaseInterval => "decrease_interval",
+            Action::IncreaseHistory => "increase_history",
+            Action::DecreaseHistory => "decrease_history",
+            Action::CycleTheme => "cycle_theme",
+            Action::ExportCsv => "export_csv",
+            Action::SelectUp => "select_up",
+            Action::SelectDown => "select_down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::SelectFirst => "select_first",
+            Action::SelectLast => "select_last",
+            Action::PreviousSortColumn => "previous_sort_column",
+            Action::NextSortColumn => "next_sort_column",
+            Action::ReverseSort => "reverse_sort",
+            Action::Filter => "filter",
+            Action::SendSignal => "send_signal",
+            Action::TogglePlayback => "toggle_playback",
+            Action::SlowerPlayback => "slower_playback",
+            Action::FasterPlayback => "faster_playback",
+            Action::SeekBackward => "seek_backward",
+            Action::SeekForward => "seek_forward",
+            Action::JumpTo => "jump_to"
+        }
+    }
+
+    // Text of the help panel
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::ToggleHelp => "Show All Keys",
+            Action::ShowCpuGraph => "Show CPU Graph",
+            Action::TogglePerCore => "Toggle Per-Core CPU Lines",
+            Action::ToggleFrequency => "Toggle CPU Frequency Graph",
+            Action::ShowMemoryGraph => "Show Memory Graph",
+            Action::ShowDiskGraph => "Show Disk Graph",
+            Action::ShowNetworkGraph => "Show Network Graph",
+            Action::ShowProcesses => "Show Processes",
+            Action::ShowAlerts => "Show Alert Log",
+            Action::NextDisk => "Highlight Next Disk",
+            Action::ExpandGraph => "Expand Graph Size",
+            Action::ReduceGraph => "Reduce Graph Size",
+            Action::IncreaseInterval => "Sample Interval x2",
+            Action::DecreaseInterval => "Sample Interval /2",
+            Action::IncreaseHistory => "History Window x2",
+            Action::DecreaseHistory => "History Window /2",
+            Action::CycleTheme => "Cycle Theme",
+            Action::ExportCsv => "Export Graphs To CSV",
+            Action::SelectUp => "Select Previous Process",
+            Action::SelectDown => "Select Next Process",
+            Action::PageUp => "Select 10 Processes Up",
+            Action::PageDown => "Select 10 Processes Down",
+            Action::SelectFirst => "Select First Process",
+            Action::SelectLast => "Select Last Process",
+            Action::PreviousSortColumn => "Sort By Previous Column",
+            Action::NextSortColumn => "Sort By Next Column",
+            Action::ReverseSort => "Reverse Sort Order",
+            Action::Filter => "Filter Processes",
+            Action::SendSignal => "Send Signal To Process",
+            Action::TogglePlayback => "Play / Pause",
+            Action::SlowerPlayback => "Slower Playback",
+            Action::FasterPlayback => "Faster Playback",
+            Action::SeekBackward => "Back 10 Seconds",
+            Action::SeekForward => "Forward 10 Seconds",
+            Action::JumpTo => "Jump To Position"
+        }
+    }
+
+    // Actions that only do something while the process table is shown.
+    // The list keys among them scroll the alert log as well
+    pub fn is_process_action(self) -> bool {
+        matches!(
+            self,
+            Action::SelectUp | Action::SelectDown | Action::PageUp | Action::PageDown |
+            Action::SelectFirst | Action::SelectLast | Action::PreviousSortColumn |
+            Action::NextSortColumn | Action::ReverseSort | Action::Filter | Action::SendSignal
+        )
+    }
+
+    // Actions that only do something while replaying a recording
+    pub fn is_replay_action(self) -> bool {
+        matches!(
+            self,
+            Action::TogglePlayback | Action::SlowerPlayback | Action::FasterPlayback |
+            Action::SeekBackward | Action::SeekForward | Action::JumpTo
+        )
+    }
+}
+
+// A key together with the modifiers held down, e.g. ctrl+c.
+// Shift is part of the character itself for printable keys ("K" rather than "shift+k")
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers
+}
+
+impl KeyChord {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    const fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    pub fn from_event(event: &KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if let KeyCode::Char(_) = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self::new(event.code, modifiers)
+    }
+
+    // "q", "K", "pagedown", "ctrl+c", "alt+shift+tab"
+    pub fn parse(chord: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = chord;
+        loop {
+            let lower = rest.to_lowercase();
+            let (modifier, prefix_len) = if lower.starts_with("ctrl+") && rest.len() > 5 {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("alt+") && rest.len() > 4 {
+                (KeyModifiers::ALT, 4)
+            } else if lower.starts_with("shift+") && rest.len() > 6 {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            modifiers.insert(modifier);
+            rest = &rest[prefix_len..];
+        }
+
+        let mut chars = rest.chars();
+        let code = if let (Some(c), None) = (chars.next(), chars.next()) {
+            // shift+k and K are the same chord
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            } else {
+                KeyCode::Char(c)
+            }
+        } else {
+            match rest.to_lowercase().as_str() {
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "insert" => KeyCode::Insert,
+                "delete" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(format!(
+                        "unknown key \"{chord}\", expected a character or a key name like \"up\", optionally prefixed by ctrl+, alt+ or shift+"
+                    ))
+                }
+            }
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Up => write!(f, "up"),
+            KeyCode::Down => write!(f, "down"),
+            KeyCode::Left => write!(f, "left"),
+            KeyCode::Right => write!(f, "right"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Insert => write!(f, "insert"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::F(n) => write!(f, "f{n}"),
+            other => write!(f, "{}", format!("{other:?}").to_lowercase())
+        }
+    }
+}
+
+const DEFAULT_BINDINGS: [(Action, KeyChord); 37] = [
+    (Action::Quit, KeyChord::key(KeyCode::Char('q'))),
+    (Action::Quit, KeyChord::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+    (Action::ToggleHelp, KeyChord::key(KeyCode::Char('?'))),
+    (Action::ShowCpuGraph, KeyChord::key(KeyCode::Char('c'))),
+    (Action::TogglePerCore, KeyChord::key(KeyCode::Char('t'))),
+    (Action::ToggleFrequency, KeyChord::key(KeyCode::Char('f'))),
+    (Action::ShowMemoryGraph, KeyChord::key(KeyCode::Char('m'))),
+    (Action::ShowDiskGraph, KeyChord::key(KeyCode::Char('d'))),
+    (Action::ShowNetworkGraph, KeyChord::key(KeyCode::Char('w'))),
+    (Action::ShowProcesses, KeyChord::key(KeyCode::Char('p'))),
+    (Action::ShowAlerts, KeyChord::key(KeyCode::Char('l'))),
+    (Action::NextDisk, KeyChord::key(KeyCode::Char('n'))),
+    (Action::ExpandGraph, KeyChord::key(KeyCode::Char('a'))),
+    (Action::ReduceGraph, KeyChord::key(KeyCode::Char('s'))),
+    (Action::IncreaseInterval, KeyChord::key(KeyCode::Char('+'))),
+    (Action::DecreaseInterval, KeyChord::key(KeyCode::Char('-'))),
+    (Action::IncreaseHistory, KeyChord::key(KeyCode::Char(']'))),
+    (Action::DecreaseHistory, KeyChord::key(KeyCode::Char('['))),
+    (Action::CycleTheme, KeyChord::key(KeyCode::Char('v'))),
+    (Action::ExportCsv, KeyChord::key(KeyCode::Char('e'))),
+    (Action::SelectUp, KeyChord::key(KeyCode::Up)),
+    (Action::SelectDown, KeyChord::key(KeyCode::Down)),
+    (Action::PageUp, KeyChord::key(KeyCode::PageUp)),
+    (Action::PageDown, KeyChord::key(KeyCode::PageDown)),
+    (Action::SelectFirst, KeyChord::key(KeyCode::Home)),
+    (Action::SelectLast, KeyChord::key(KeyCode::End)),
+    (Action::PreviousSortColumn, KeyChord::key(KeyCode::Char('<'))),
+    (Action::NextSortColumn, KeyChord::key(KeyCode::Char('>'))),
+    (Action::ReverseSort, KeyChord::key(KeyCode::Char('r'))),
+    (Action::Filter, KeyChord::key(KeyCode::Char('/'))),
+    (Action::SendSignal, KeyChord::key(KeyCode::Char('k'))),
+    (Action::TogglePlayback, KeyChord::key(KeyCode::Char(' '))),
+    (Action::SlowerPlayback, KeyChord::key(KeyCode::Char(','))),
+    (Action::FasterPlayback, KeyChord::key(KeyCode::Char('.'))),
+    (Action::SeekBackward, KeyChord::key(KeyCode::Left)),
+    (Action::SeekForward, KeyChord::key(KeyCode::Right)),
+    (Action::JumpTo, KeyChord::key(KeyCode::Char('g')))
+];
+
+// Keys bound to an action in the config file: either one chord or a list of them
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>)
+}
+
+impl KeyBinding {
+    fn chords(&self) -> Vec<&str> {
+        match self {
+            KeyBinding::One(chord) => vec![chord.as_str()],
+            KeyBinding::Many(chords) => chords.iter().map(String::as_str).collect()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct KeyMap {
+    bindings: HashMap<KeyChord, Action>
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS.iter().map(|(action, chord)| (*chord, *action)).collect()
+        }
+    }
+}
+
+impl KeyMap {
+    // Defaults, except for the actions listed in the config file which get exactly the keys given there.
+    // An empty list unbinds an action
+    pub fn with_overrides(overrides: &HashMap<Action, KeyBinding>) -> Result<Self, String> {
+        let mut chords: Vec<(Action, KeyChord)> = DEFAULT_BINDINGS.iter()
+            .filter(|(action, _)| !overrides.contains_key(action))
+            .copied()
+            .collect();
+        // In Action::ALL order rather than the map's, so that the same config always reports the same conflict
+        for action in Action::ALL {
+            if let Some(binding) = overrides.get(&action) {
+                for chord in binding.chords() {
+                    chords.push((action, KeyChord::parse(chord)?));
+                }
+            }
+        }
+
+        let mut bindings: HashMap<KeyChord, Action> = HashMap::new();
+        for (action, chord) in chords {
+            if let Some(other) = bindings.insert(chord, action) {
+                if other != action {
+                    return Err(format!("\"{chord}\" is bound to both {} and {}", other.name(), action.name()));
+                }
+            }
+        }
+        Ok(Self { bindings })
+    }
+
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings.get(&KeyChord::from_event(event)).copied()
+    }
+
+    // Every chord bound to the action, in a stable order for the help panel
+    pub fn chords(&self, action: Action) -> Vec<KeyChord> {
+        let mut chords: Vec<KeyChord> = self.bindings.iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(chord, _)| *chord)
+            .collect();
+        // Plain keys before chords with modifiers
+        chords.sort_by_key(|chord| (chord.modifiers.bits(), chord.to_string()));
+        chords
+    }
+}
@@ -2,26 +2,127 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
-use std::{io, thread, time::Duration};
+use std::{
+    io, panic, process, thread,
+    sync::{Arc, atomic::{AtomicBool, Ordering}},
+    time::Duration
+};
 use ratatui::{
     Terminal,
     backend::{CrosstermBackend, Backend},
+    layout::Rect
 };
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event::Key, KeyCode, KeyEvent},
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event::{Key, Mouse, Resize}, KeyCode, KeyEvent,
+        KeyEventKind, MouseButton, MouseEvent, MouseEventKind
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen}
 };
-use state::Graph;
+use state::{Graph, Panel};
 
+mod alerts;
+mod cli;
+mod config;
+mod export;
+mod exporter;
+mod keymap;
+mod metrics;
+mod recorder;
+mod replay;
+mod report;
+mod settings;
 mod sys_poller;
+mod sampler;
 mod state;
+mod theme;
 mod ui;
 
-use crate::state::State;
+use clap::Parser;
+use crate::{
+    cli::{Cli, Command},
+    config::Config,
+    keymap::Action,
+    recorder::Recorder,
+    replay::Replay,
+    sampler::Sampler,
+    settings::Settings,
+    state::State
+};
+
+// How often the UI is redrawn. Sampling runs on its own thread at its own interval
+const FRAME_INTERVAL: Duration = Duration::from_millis(50);
 
 
 fn main() -> Result<(), io::Error> {
+    // Parse flags before touching the terminal so that --help and errors print normally
+    let cli = Cli::parse();
+    let mut config = match Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("sys_tui: {e}");
+            process::exit(1);
+        }
+    };
+    cli.override_settings(&mut config.settings);
+
+    match &cli.command {
+        Some(Command::Snapshot(_) | Command::Serve(_)) if cli.record.is_some() => {
+            eprintln!("sys_tui: --record can only be used with the interface");
+            process::exit(1);
+        },
+        Some(Command::Snapshot(args)) => {
+            if let Err(e) = report::print_snapshots(sys_poller::setup(), &config.settings, args) {
+                eprintln!("sys_tui: {e}");
+                process::exit(1);
+            }
+            return Ok(());
+        },
+        Some(Command::Serve(args)) => {
+            if let Err(e) = exporter::serve(sys_poller::setup(), &config.settings, args.listen) {
+                eprintln!("sys_tui: {e}");
+                process::exit(1);
+            }
+            return Ok(());
+        },
+        Some(Command::Replay(_)) | None => {}
+    }
+
+    // Before the sampler thread starts, it can panic before the first frame is drawn
+    install_panic_hook();
+
+    // Files are opened up front so that a bad path is reported before the screen is taken over
+    let state = match (&cli.command, &cli.record) {
+        (Some(Command::Replay(_)), Some(_)) => {
+            eprintln!("sys_tui: --record cannot be used while replaying");
+            process::exit(1);
+        },
+        (Some(Command::Replay(args)), None) => match Replay::load(&args.file) {
+            Ok(replay) => State::new_replay(replay, &config),
+            Err(e) => {
+                eprintln!("sys_tui: cannot replay {}: {e}", args.file.display());
+                process::exit(1);
+            }
+        },
+        (_, record) => {
+            let mut state = State::new(sys_poller::setup(), &config);
+            if let Some(path) = record {
+                match Recorder::open(path) {
+                    Ok(recorder) => state.start_recording(recorder),
+                    Err(e) => {
+                        eprintln!("sys_tui: cannot record to {}: {e}", path.display());
+                        process::exit(1);
+                    }
+                }
+            }
+            state
+        }
+    };
+
+    let shutdown = register_shutdown_signals()?;
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -30,54 +131,223 @@ fn main() -> Result<(), io::Error> {
     let mut terminal = Terminal::new(backend)?;
 
     // mutable reference of terminal to run_app
-    let result = run_app(&mut terminal);
+    let result = run_app(&mut terminal, state, &shutdown);
 
     // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    restore_terminal()?;
 
     // If app executed into error
     if let Err(e) = result {
-        println!("{}", e.to_string());
+        println!("{}", e);
     }
 
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
-    let mut state: State = State::new(sys_poller::setup());
-    let mut elapsed_ms: f64 = state.refresh();
-    loop {
-        // Draw data on the terminal and sleep for 10 ms
-        terminal.draw(|f| ui::create_ui(f, &mut state, elapsed_ms))?;
-        // thread::sleep(Duration::from_millis(490));
+// Leave the terminal the way we found it
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        cursor::Show
+    )
+}
+
+// A panic would otherwise leave the terminal in raw mode on the alternate screen, with the
+// message printed where nobody can read it. Panics on the sampler thread end the app as well
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+        process::exit(101);
+    }));
+}
 
-        // Refresh state before next loop
+#[cfg(unix)]
+const SHUTDOWN_SIGNALS: [i32; 3] = [
+    signal_hook::consts::SIGINT,
+    signal_hook::consts::SIGTERM,
+    signal_hook::consts::SIGHUP
+];
+#[cfg(not(unix))]
+const SHUTDOWN_SIGNALS: [i32; 2] = [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM];
+
+// The returned flag is set when one of the signals arrives, run_app then stops like on quit
+// so that main restores the terminal. A second signal exits right away in case the app hangs,
+// restoring the terminal first. That is done on a thread since it isn't safe in a signal handler
+fn register_shutdown_signals() -> io::Result<Arc<AtomicBool>> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let mut signals = signal_hook::iterator::Signals::new(SHUTDOWN_SIGNALS)?;
+    let flag = Arc::clone(&shutdown);
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            if flag.swap(true, Ordering::Relaxed) {
+                let _ = restore_terminal();
+                process::exit(1);
+            }
+        }
+    });
+    Ok(shutdown)
+}
+
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut state: State, shutdown: &AtomicBool) -> io::Result<()> {
+    while !shutdown.load(Ordering::Relaxed) {
+        // Take in new snapshots from the sampler thread, if any
         // This will add new data to datasets etc.
-        elapsed_ms = state.refresh();
-        
-        // Quit if user presses 'q'
-        match event::poll(Duration::from_millis(50))? {
+        let elapsed_ms: f64 = state.refresh();
+
+        terminal.draw(|f| ui::create_ui(f, &mut state, elapsed_ms))?;
+
+        // Wait for input until the next frame is due
+        match event::poll(FRAME_INTERVAL)? {
             true => {
-                if let Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Char('q') => break,
-                        KeyCode::Char('c') => state.set_graph_cpu(),
-                        KeyCode::Char('m') => state.set_graph_memory(),
-                        KeyCode::Char('d') => state.set_graph_disk(),
-                        KeyCode::Char('a') => state.expand_graph_size(),
-                        KeyCode::Char('s') => state.reduce_graph_size(),
-                        _ => {}
+                match event::read()? {
+                    // Windows reports releases as well, toggles would undo themselves.
+                    // Holding a key down repeats it
+                    Key(key) if key.kind == KeyEventKind::Release => {},
+                    Key(key) => {
+                        // The signal dialog takes every key until it is closed
+                        if let Some(dialog) = state.signal_dialog.as_mut() {
+                            match key.code {
+                                KeyCode::Up => dialog.move_selection(-1),
+                                KeyCode::Down => dialog.move_selection(1),
+                                KeyCode::Enter | KeyCode::Char('y') => state.confirm_signal_dialog(),
+                                KeyCode::Esc | KeyCode::Char('n') => state.close_signal_dialog(),
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        // So does the key list, any key not used for scrolling closes it
+                        if state.help_offset.is_some() {
+                            match key.code {
+                                KeyCode::Up => state.scroll_help(-1),
+                                KeyCode::Down => state.scroll_help(1),
+                                KeyCode::PageUp => state.scroll_help(-10),
+                                KeyCode::PageDown => state.scroll_help(10),
+                                KeyCode::Home => state.help_offset = Some(0),
+                                KeyCode::End => state.scroll_help(isize::MAX),
+                                _ => state.help_offset = None
+                            }
+                            continue;
+                        }
+
+                        // While typing a process filter every key goes to the filter
+                        if state.process_view.filtering {
+                            match key.code {
+                                KeyCode::Esc => state.process_view.clear_filter(),
+                                KeyCode::Enter => state.process_view.stop_filtering(),
+                                KeyCode::Backspace => state.process_view.pop_filter_char(),
+                                KeyCode::Char(c) => state.process_view.push_filter_char(c),
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        // Same for the position typed after the replay jump key
+                        if let Some(replay) = state.replay.as_mut().filter(|replay| replay.jump_input.is_some()) {
+                            match key.code {
+                                KeyCode::Esc => replay.cancel_jump(),
+                                KeyCode::Enter => state.confirm_replay_jump(),
+                                KeyCode::Backspace => replay.pop_jump_char(),
+                                KeyCode::Char(c) => replay.push_jump_char(c),
+                                _ => {}
+                            }
+                            continue;
+                        }
+
+                        match state.keymap.action(&key) {
+                            Some(Action::Quit) => break,
+                            Some(action) => state.dispatch(action),
+                            None => {}
+                        }
                     }
+                    Mouse(mouse) => handle_mouse(&mut state, mouse),
+                    // Resize the buffers right away so the next frame is laid out for the new size.
+                    // A drag in progress refers to the old layout
+                    Resize(_, _) => {
+                        terminal.autoresize()?;
+                        state.resizing_graph = false;
+                    },
+                    _ => {}
                 }
             }
             false => continue
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+fn contains(area: &Rect, column: u16, row: u16) -> bool {
+    (area.left()..area.right()).contains(&column) && (area.top()..area.bottom()).contains(&row)
+}
+
+fn handle_mouse(state: &mut State, mouse: MouseEvent) {
+    // The signal dialog is modal, the mouse does nothing until it is closed
+    if state.signal_dialog.is_some() {
+        return;
+    }
+    // Same for the key list, apart from scrolling it
+    if state.help_offset.is_some() {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => state.scroll_help(1),
+            MouseEventKind::ScrollUp => state.scroll_help(-1),
+            _ => {}
+        }
+        return;
+    }
+
+    let (column, row) = (mouse.column, mouse.row);
+    let area = |name: &str| state.areas.get(name).copied().unwrap_or_default();
+    let graph_area = area("graph_area");
+    let info_area = area("info_area");
+    // On narrow screens the graph is below the info blocks rather than next to them
+    let stacked = info_area.bottom() <= graph_area.top();
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            // The graph's border and the info section's border sit side by side.
+            // When stacked the graph's top border holds the tabs, so only the info border drags
+            let on_border = if stacked {
+                row + 1 == graph_area.y && (graph_area.left()..graph_area.right()).contains(&column)
+            } else {
+                (graph_area.x.saturating_sub(1)..=graph_area.x).contains(&column)
+                    && (graph_area.top()..graph_area.bottom()).contains(&row)
+            };
+            if on_border {
+                state.resizing_graph = true;
+            } else if row == graph_area.y && contains(&graph_area, column, row) {
+                if let Some(graph) = ui::graph_tab_at(&graph_area, column) {
+                    state.set_graph(graph);
+                }
+                state.focused = Some(Panel::Graph);
+            } else if let Some(panel) = Panel::ALL.iter().find(|panel| contains(&area(panel.area_name()), column, row)) {
+                state.focus_panel(*panel);
+            }
+        },
+        MouseEventKind::Drag(MouseButton::Left) if state.resizing_graph => {
+            // The graph spans from the dragged border to the far edge of the info + graph section
+            let (section_size, graph_size) = if stacked {
+                (graph_area.bottom().saturating_sub(info_area.y), graph_area.bottom().saturating_sub(row))
+            } else {
+                (graph_area.right().saturating_sub(info_area.x), graph_area.right().saturating_sub(column))
+            };
+            state.set_graph_size_percentage((graph_size as u32 * 100 / section_size.max(1) as u32) as u16);
+        },
+        MouseEventKind::Up(MouseButton::Left) => state.resizing_graph = false,
+        MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+            let delta: isize = if mouse.kind == MouseEventKind::ScrollDown { 1 } else { -1 };
+            if contains(&area("disk_info"), column, row) {
+                state.scroll_disk_table(delta);
+            } else if contains(&graph_area, column, row) && state.graph == Graph::PROCESS {
+                state.process_view.move_selection(delta * 3);
+            } else if contains(&graph_area, column, row) && state.graph == Graph::ALERTS {
+                state.alerts.scroll_log(delta * 3);
+            }
+        },
+        _ => {}
+    }
+}
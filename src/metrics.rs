@@ -0,0 +1,211 @@
+use std::{io, time::{SystemTime, UNIX_EPOCH}};
+use sysinfo::Signal;
+use crate::sys_poller::{DiskData, NetworkData, ProcessData, Snapshot};
+
+// Where samples come from. SysInfo reads the machine the app runs on,
+// tests use a ScriptedSource that plays back fixed data
+pub trait MetricsSource {
+    // Take a new sample, the accessors below report on the latest one
+    fn refresh(&mut self);
+
+    // CPU
+    fn get_cpu_brand(&self) -> Option<String>;
+    fn get_core_count(&self) -> Option<usize>;
+    fn get_avg_cpu_usage(&self) -> f64;
+    fn get_per_core_usage(&self) -> Vec<f64>;
+    fn get_avg_cpu_frequency(&self) -> Option<u64>;
+    // Current frequency of every core in MHz
+    fn get_per_core_frequency(&self) -> Vec<u64>;
+
+    // Memory, (free, used, total) in MB
+    fn get_memory_data(&self) -> (u64, u64, u64);
+    fn get_swap_data(&self) -> (u64, u64, u64);
+
+    fn get_disk_data(&self) -> Vec<DiskData>;
+    fn get_network_data(&self) -> Vec<NetworkData>;
+    fn get_process_data(&self) -> Vec<ProcessData>;
+    fn send_signal(&self, pid: u32, signal: Signal) -> io::Result<()>;
+
+    // Milliseconds since the unix epoch when the latest sample was taken
+    fn get_timestamp_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64)
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            timestamp_ms: self.get_timestamp_ms(),
+            cpu_brand: self.get_cpu_brand(),
+            core_count: self.get_core_count(),
+            avg_cpu_usage: self.get_avg_cpu_usage(),
+            per_core_usage: self.get_per_core_usage(),
+            avg_cpu_frequency: self.get_avg_cpu_frequency(),
+            per_core_frequency: self.get_per_core_frequency(),
+            memory: self.get_memory_data(),
+            swap: self.get_swap_data(),
+            disks: self.get_disk_data(),
+            networks: self.get_network_data(),
+            processes: self.get_process_data()
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod scripted {
+    use std::{io, sync::{Arc, Mutex}};
+    use sysinfo::Signal;
+    use crate::sys_poller::{DiskData, NetworkData, ProcessData, Snapshot};
+    use super::MetricsSource;
+
+    // Plays back a fixed list of snapshots, one per refresh, then keeps repeating the last one.
+    // Signals are recorded instead of sent
+    pub struct ScriptedSource {
+        frames: Vec<Snapshot>,
+        current: Option<usize>,
+        pub sent_signals: Arc<Mutex<Vec<(u32, Signal)>>>
+    }
+
+    impl ScriptedSource {
+        pub fn new(frames: Vec<Snapshot>) -> Self {
+            assert!(!frames.is_empty(), "a scripted source needs at least one frame");
+            Self {
+                frames,
+                current: None,
+                sent_signals: Arc::new(Mutex::new(Vec::new()))
+            }
+        }
+
+        fn frame(&self) -> &Snapshot {
+            &self.frames[self.current.unwrap_or(0)]
+        }
+    }
+
+    impl MetricsSource for ScriptedSource {
+        fn refresh(&mut self) {
+            self.current = Some(self.current.map_or(0, |i| (i + 1).min(self.frames.len() - 1)));
+        }
+
+        fn get_cpu_brand(&self) -> Option<String> {
+            self.frame().cpu_brand.clone()
+        }
+
+        fn get_core_count(&self) -> Option<usize> {
+            self.frame().core_count
+        }
+
+        fn get_avg_cpu_usage(&self) -> f64 {
+            self.frame().avg_cpu_usage
+        }
+
+        fn get_per_core_usage(&self) -> Vec<f64> {
+            self.frame().per_core_usage.clone()
+        }
+
+        fn get_avg_cpu_frequency(&self) -> Option<u64> {
+            self.frame().avg_cpu_frequency
+        }
+
+        fn get_per_core_frequency(&self) -> Vec<u64> {
+            self.frame().per_core_frequency.clone()
+        }
+
+        fn get_memory_data(&self) -> (u64, u64, u64) {
+            self.frame().memory
+        }
+
+        fn get_swap_data(&self) -> (u64, u64, u64) {
+            self.frame().swap
+        }
+
+        fn get_disk_data(&self) -> Vec<DiskData> {
+            self.frame().disks.clone()
+        }
+
+        fn get_network_data(&self) -> Vec<NetworkData> {
+            self.frame().networks.clone()
+        }
+
+        fn get_process_data(&self) -> Vec<ProcessData> {
+            self.frame().processes.clone()
+        }
+
+        fn send_signal(&self, pid: u32, signal: Signal) -> io::Result<()> {
+            if !self.frame().processes.iter().any(|p| p.pid == pid) {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "no such process"));
+            }
+            self.sent_signals.lock().unwrap().push((pid, signal));
+            Ok(())
+        }
+
+        fn get_timestamp_ms(&self) -> u64 {
+            self.frame().timestamp_ms
+        }
+    }
+}
+
+// Parts of the snapshots tests build, with everything they don't care about filled in
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use std::{ffi::OsString, time::Duration};
+    use sysinfo::DiskKind;
+    use crate::{
+        alerts::{AlertRule, Comparison, Metric},
+        sys_poller::{DiskData, NetworkData, ProcessData}
+    };
+
+    // An ext4 SSD, sizes and totals in bytes
+    pub fn disk(name: &str, mount_point: &str, total_space: u64, available_space: u64, read_bytes: u64, written_bytes: u64) -> DiskData {
+        DiskData::new(
+            Box::new(OsString::from(name)),
+            DiskKind::SSD,
+            Box::new(OsString::from("ext4")),
+            total_space,
+            available_space,
+            Box::new(OsString::from(mount_point)),
+            read_bytes,
+            written_bytes
+        )
+    }
+
+    // One packet per 1000 bytes, no errors
+    pub fn network(name: &str, received: u64, transmitted: u64) -> NetworkData {
+        NetworkData {
+            name: name.to_string(),
+            total_received: received,
+            total_transmitted: transmitted,
+            total_packets_received: received / 1000,
+            total_packets_transmitted: transmitted / 1000,
+            total_errors_on_received: 0,
+            total_errors_on_transmitted: 0
+        }
+    }
+
+    // A running root process started from /usr/bin, memory in bytes
+    pub fn process(pid: u32, name: &str, cpu_usage: f32, memory: u64) -> ProcessData {
+        ProcessData {
+            pid,
+            name: name.to_string(),
+            user: "root".to_string(),
+            cpu_usage,
+            memory,
+            total_read_bytes: 0,
+            total_written_bytes: 0,
+            status: "Run".to_string(),
+            command: format!("/usr/bin/{name}")
+        }
+    }
+
+    // Named the way the config names rules without a name, with no command
+    pub fn alert_rule(metric: Metric, comparison: Comparison, duration_secs: u64) -> AlertRule {
+        let mut rule = AlertRule {
+            name: String::new(),
+            metric,
+            comparison,
+            duration: Duration::from_secs(duration_secs),
+            command: None
+        };
+        rule.name = rule.describe();
+        rule
+    }
+}
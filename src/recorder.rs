@@ -0,0 +1,165 @@
+use std::{
+    ffi::OsString,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf}
+};
+use serde::{Deserialize, Serialize};
+use sysinfo::DiskKind;
+use crate::sys_poller::{DiskData, NetworkData, Snapshot};
+
+// One line of a recording. Processes are left out to keep the file small,
+// everything the graphs are drawn from is kept
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    // Milliseconds since the unix epoch
+    pub timestamp_ms: u64,
+    pub cpu_brand: Option<String>,
+    pub core_count: Option<usize>,
+    pub avg_cpu_usage: f64,
+    pub per_core_usage: Vec<f64>,
+    pub avg_cpu_frequency: Option<u64>,
+    pub per_core_frequency: Vec<u64>,
+    // (free, used, total) in MB
+    pub memory: (u64, u64, u64),
+    pub swap: (u64, u64, u64),
+    pub disks: Vec<DiskRecord>,
+    pub networks: Vec<NetworkData>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DiskRecord {
+    pub name: String,
+    pub kind: String,
+    pub file_system: String,
+    pub mount_point: String,
+    pub total_space: u64,
+    pub available_space: u64,
+    pub read_bytes: u64,
+    pub written_bytes: u64
+}
+
+impl Record {
+    pub fn from_snapshot(snapshot: &Snapshot) -> Self {
+        Self {
+            timestamp_ms: snapshot.timestamp_ms,
+            cpu_brand: snapshot.cpu_brand.clone(),
+            core_count: snapshot.core_count,
+            avg_cpu_usage: snapshot.avg_cpu_usage,
+            per_core_usage: snapshot.per_core_usage.clone(),
+            avg_cpu_frequency: snapshot.avg_cpu_frequency,
+            per_core_frequency: snapshot.per_core_frequency.clone(),
+            memory: snapshot.memory,
+            swap: snapshot.swap,
+            disks: snapshot.disks.iter().map(|d| DiskRecord {
+                name: d.name.to_string_lossy().into_owned(),
+                kind: d.kind.to_string(),
+                file_system: d.file_system.to_string_lossy().into_owned(),
+                mount_point: d.mount_point.to_string_lossy().into_owned(),
+                total_space: d.total_space,
+                available_space: d.available_space,
+                read_bytes: d.read_bytes,
+                written_bytes: d.written_bytes
+            }).collect(),
+            networks: snapshot.networks.clone()
+        }
+    }
+
+    pub fn into_snapshot(self) -> Snapshot {
+        Snapshot {
+            timestamp_ms: self.timestamp_ms,
+            cpu_brand: self.cpu_brand,
+            core_count: self.core_count,
+            avg_cpu_usage: self.avg_cpu_usage,
+            per_core_usage: self.per_core_usage,
+            avg_cpu_frequency: self.avg_cpu_frequency,
+            per_core_frequency: self.per_core_frequency,
+            memory: self.memory,
+            swap: self.swap,
+            disks: self.disks.into_iter().map(|d| DiskData::new(
+                Box::new(OsString::from(d.name)),
+                parse_disk_kind(&d.kind),
+                Box::new(OsString::from(d.file_system)),
+                d.total_space,
+                d.available_space,
+                Box::new(OsString::from(d.mount_point)),
+                d.read_bytes,
+                d.written_bytes
+            )).collect(),
+            networks: self.networks,
+            processes: Vec::new()
+        }
+    }
+}
+
+// Inverse of DiskKind's Display
+fn parse_disk_kind(kind: &str) -> DiskKind {
+    match kind {
+        "SSD" => DiskKind::SSD,
+        "HDD" => DiskKind::HDD,
+        _ => DiskKind::Unknown(-1)
+    }
+}
+
+// Appends every sample to a file, one JSON object per line. Each line is flushed right away
+// so that the recording is complete up to the last sample even if the app is killed
+pub struct Recorder {
+    path: PathBuf,
+    writer: BufWriter<File>
+}
+
+impl Recorder {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { path: path.to_path_buf(), writer: BufWriter::new(file) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &Record::from_snapshot(snapshot))?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::*;
+    use crate::metrics::fixtures::disk;
+
+    #[test]
+    fn records_are_appended_and_read_back() {
+        let path = std::env::temp_dir().join(format!("sys_tui_recorder_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let snapshot = |timestamp_ms| Snapshot {
+            timestamp_ms,
+            avg_cpu_usage: 42.0,
+            per_core_usage: vec![40.0, 44.0],
+            memory: (1000, 3000, 4000),
+            disks: vec![disk("sda1", "/", 1000, 400, 10, 20)],
+            ..Snapshot::default()
+        };
+
+        // A second recorder on the same file appends instead of truncating
+        Recorder::open(&path).unwrap().write(&snapshot(1000)).unwrap();
+        Recorder::open(&path).unwrap().write(&snapshot(2000)).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let snapshots: Vec<Snapshot> = contents.lines()
+            .map(|line| serde_json::from_str::<Record>(line).unwrap().into_snapshot())
+            .collect();
+
+        assert_eq!(snapshots.iter().map(|s| s.timestamp_ms).collect::<Vec<_>>(), vec![1000, 2000]);
+        assert_eq!(snapshots[1].per_core_usage, vec![40.0, 44.0]);
+        assert_eq!(snapshots[1].memory, (1000, 3000, 4000));
+        assert_eq!(snapshots[1].disks[0].kind, DiskKind::SSD);
+        assert_eq!(snapshots[1].disks[0].mount_point.to_string_lossy(), "/");
+        assert_eq!(snapshots[1].disks[0].written_bytes, 20);
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::Path,
+    time::{Duration, Instant}
+};
+use crate::{recorder::Record, sys_poller::Snapshot};
+
+// Playback speeds the user steps through, 1x is where a replay starts
+pub const SPEEDS: [f64; 6] = [0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+const NORMAL_SPEED: usize = 1;
+// How far the arrow keys move the playback position
+pub const SEEK_STEP: Duration = Duration::from_secs(10);
+
+// Plays back a file written with --record. Playback has its own clock, running at the chosen
+// speed while playing. Every snapshot whose timestamp the clock has passed is handed to State
+pub struct Replay {
+    snapshots: Vec<Snapshot>,
+    // Timestamp of the recording the clock is at, in ms since the unix epoch
+    position_ms: u64,
+    // Index of the first snapshot not handed out yet
+    next: usize,
+    pub playing: bool,
+    speed: usize,
+    last_tick: Option<Instant>,
+    // Text typed after pressing the jump key, None when not typing
+    pub jump_input: Option<String>
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut snapshots = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record = serde_json::from_str(&line).map_err(|e| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {e}", i + 1)
+            ))?;
+            snapshots.push(record.into_snapshot());
+        }
+        Self::new(snapshots).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the recording is empty"))
+    }
+
+    // None without any snapshot to play
+    pub fn new(mut snapshots: Vec<Snapshot>) -> Option<Self> {
+        // Recordings are appended to, a file can hold several sessions one after the other
+        snapshots.sort_by_key(|s| s.timestamp_ms);
+        let position_ms = snapshots.first()?.timestamp_ms;
+        Some(Self {
+            snapshots,
+            position_ms,
+            next: 0,
+            playing: true,
+            speed: NORMAL_SPEED,
+            last_tick: None,
+            jump_input: None
+        })
+    }
+
+    pub fn start_ms(&self) -> u64 {
+        self.snapshots[0].timestamp_ms
+    }
+
+    pub fn end_ms(&self) -> u64 {
+        self.snapshots[self.snapshots.len() - 1].timestamp_ms
+    }
+
+    pub fn position_ms(&self) -> u64 {
+        self.position_ms
+    }
+
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    // Move the clock forward by the time passed since the last call, scaled by the speed,
+    // and return the snapshots it went past. Playback pauses at the end of the recording
+    pub fn advance(&mut self, now: Instant) -> &[Snapshot] {
+        let last_tick = self.last_tick.replace(now).unwrap_or(now);
+        if self.playing {
+            let step_ms = now.saturating_duration_since(last_tick).as_secs_f64() * 1000.0 * self.speed();
+            self.position_ms = (self.position_ms + step_ms as u64).min(self.end_ms());
+            if self.at_end() {
+                self.playing = false;
+            }
+        }
+
+        let first = self.next;
+        while self.next < self.snapshots.len() && self.snapshots[self.next].timestamp_ms <= self.position_ms {
+            self.next += 1;
+        }
+        &self.snapshots[first..self.next]
+    }
+
+    // Jump to a timestamp within the recording and return what the graphs should hold there:
+    // the snapshots of the history window before it, or at least the latest one
+    pub fn seek(&mut self, position_ms: u64, history: Duration) -> &[Snapshot] {
+        self.position_ms = position_ms.clamp(self.start_ms(), self.end_ms());
+        self.next = self.snapshots.partition_point(|s| s.timestamp_ms <= self.position_ms);
+
+        let window_start_ms = self.position_ms.saturating_sub(history.as_millis() as u64);
+        let first = self.snapshots[..self.next]
+            .partition_point(|s| s.timestamp_ms < window_start_ms)
+            .min(self.next.saturating_sub(1));
+        &self.snapshots[first..self.next]
+    }
+
+    pub fn seek_by(&mut self, offset_ms: i64, history: Duration) -> &[Snapshot] {
+        let position_ms = self.position_ms.saturating_add_signed(offset_ms);
+        self.seek(position_ms, history)
+    }
+
+    pub fn toggle_playing(&mut self) {
+        self.playing = !self.playing;
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.position_ms == self.end_ms()
+    }
+
+    pub fn push_jump_char(&mut self, c: char) {
+        if let Some(input) = &mut self.jump_input {
+            input.push(c);
+        }
+    }
+
+    pub fn pop_jump_char(&mut self) {
+        if let Some(input) = &mut self.jump_input {
+            input.pop();
+        }
+    }
+
+    pub fn cancel_jump(&mut self) {
+        self.jump_input = None;
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+}
+
+// Position typed after the jump key, as the timestamp to go to. Either an offset from the start
+// of the recording, "90" (seconds), "1:30" (minutes and seconds) or "+1:01:30",
+// a time of day in UTC like the status line shows, "22:13:20", or an RFC 3339 time
+pub fn parse_position(input: &str, start_ms: u64) -> Option<u64> {
+    const DAY_MS: u64 = 86_400_000;
+
+    let input = input.trim();
+    if let Some(offset) = input.strip_prefix('+') {
+        return Some(start_ms + parse_offset(offset)?.as_millis() as u64);
+    }
+    if let Some(timestamp_ms) = parse_rfc3339(input) {
+        return Some(timestamp_ms);
+    }
+
+    let time = input.strip_suffix("UTC").unwrap_or(input).trim_end();
+    if time.split(':').count() != 3 {
+        return Some(start_ms + parse_offset(input)?.as_millis() as u64);
+    }
+    // On the day the recording starts, or the next one for recordings going past midnight
+    let time_ms = parse_time_of_day(time)?.as_millis() as u64;
+    let timestamp_ms = start_ms / DAY_MS * DAY_MS + time_ms;
+    Some(if timestamp_ms < start_ms { timestamp_ms + DAY_MS } else { timestamp_ms })
+}
+
+// "90", "1:30" or "1:01:30"
+pub fn parse_offset(input: &str) -> Option<Duration> {
+    let parts: Vec<&str> = input.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut seconds: u64 = 0;
+    for part in parts {
+        seconds = seconds.checked_mul(60)?.checked_add(part.parse().ok()?)?;
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+// "22:13:20", hours under 24 and minutes and seconds under 60
+fn parse_time_of_day(input: &str) -> Option<Duration> {
+    let parts: Vec<u64> = input.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [hours, minutes, seconds] if hours < 24 && minutes < 60 && seconds < 60 => {
+            Some(Duration::from_secs(hours * 3600 + minutes * 60 + seconds))
+        },
+        _ => None
+    }
+}
+
+// "2023-11-14T22:13:20Z", "2023-11-14 23:13:20.500+01:00", in milliseconds since the epoch
+fn parse_rfc3339(input: &str) -> Option<u64> {
+    let (date, time) = input.split_once(['T', 't', ' '])?;
+    let date: Vec<i64> = date.split('-').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let [year, month, day] = date[..] else {
+        return None;
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (time, offset_secs) = match time.strip_suffix(['Z', 'z']) {
+        Some(time) => (time, 0),
+        None => {
+            // ±hh:mm ahead of UTC
+            let (time, offset) = time.split_at(time.rfind(['+', '-'])?);
+            let offset_secs = parse_time_of_day(&format!("{}:00", &offset[1..]))?.as_secs() as i64;
+            (time, if offset.starts_with('-') { -offset_secs } else { offset_secs })
+        }
+    };
+    // Fractions of a second past the milliseconds are dropped
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let millis: i64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<3}")[..3].parse().ok()?
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400
+        + parse_time_of_day(time)?.as_secs() as i64
+        - offset_secs;
+    u64::try_from(seconds * 1000 + millis).ok()
+}
+
+// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// 1:01:30 or 01:30 for durations under an hour
+pub fn format_offset(offset: Duration) -> String {
+    let seconds = offset.as_secs();
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+// Wall clock time of a recorded sample. There is no time zone database to go by, so UTC
+pub fn format_utc_time(timestamp_ms: u64) -> String {
+    let seconds = timestamp_ms / 1000 % 86_400;
+    format!("{:02}:{:02}:{:02} UTC", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(timestamps: &[u64]) -> Replay {
+        let snapshots = timestamps.iter()
+            .map(|timestamp_ms| Snapshot { timestamp_ms: *timestamp_ms, ..Snapshot::default() })
+            .collect();
+        Replay::new(snapshots).unwrap()
+    }
+
+    fn timestamps(snapshots: &[Snapshot]) -> Vec<u64> {
+        snapshots.iter().map(|s| s.timestamp_ms).collect()
+    }
+
+    #[test]
+    fn the_clock_runs_at_the_chosen_speed() {
+        let mut replay = replay(&[10_000, 11_000, 12_000, 13_000, 14_000]);
+        let start = Instant::now();
+        assert_eq!(timestamps(replay.advance(start)), vec![10_000]);
+
+        replay.faster();
+        assert_eq!(replay.speed(), 2.0);
+        assert_eq!(timestamps(replay.advance(start + Duration::from_millis(1000))), vec![11_000, 12_000]);
+
+        replay.toggle_playing();
+        assert!(replay.advance(start + Duration::from_millis(5000)).is_empty());
+
+        // Past the end playback stops on the last snapshot
+        replay.toggle_playing();
+        assert_eq!(timestamps(replay.advance(start + Duration::from_millis(9000))), vec![13_000, 14_000]);
+        assert!(!replay.playing);
+    }
+
+    #[test]
+    fn seeking_returns_the_history_window() {
+        let mut replay = replay(&[10_000, 20_000, 30_000, 40_000, 50_000]);
+        assert_eq!(timestamps(replay.seek(45_000, Duration::from_secs(25))), vec![20_000, 30_000, 40_000]);
+        assert_eq!(replay.position_ms(), 45_000);
+
+        // Nothing recorded within the window, the latest snapshot is still shown
+        assert_eq!(timestamps(replay.seek_by(-10_000, Duration::from_secs(5))), vec![30_000]);
+
+        // Playing on from there picks up with the next snapshot
+        let now = Instant::now();
+        replay.advance(now);
+        assert_eq!(timestamps(replay.advance(now + Duration::from_secs(10))), vec![40_000]);
+
+        assert_eq!(timestamps(replay.seek(0, Duration::from_secs(25))), vec![10_000]);
+        assert_eq!(replay.position_ms(), 10_000);
+    }
+
+    #[test]
+    fn offsets_are_parsed_and_formatted() {
+        assert_eq!(parse_offset("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_offset("1:30"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_offset("1:01:30"), Some(Duration::from_secs(3690)));
+        assert_eq!(parse_offset("1:x"), None);
+        assert_eq!(parse_offset("1:2:3:4"), None);
+
+        assert_eq!(format_offset(Duration::from_secs(90)), "01:30");
+        assert_eq!(format_offset(Duration::from_secs(3690)), "1:01:30");
+        assert_eq!(format_utc_time(1_700_000_000_000), "22:13:20 UTC");
+    }
+
+    #[test]
+    fn positions_are_resolved_against_the_recording() {
+        // 2023-11-14 22:13:20 UTC
+        let start_ms = 1_700_000_000_000;
+        assert_eq!(parse_position("90", start_ms), Some(start_ms + 90_000));
+        assert_eq!(parse_position(" +1:01:30 ", start_ms), Some(start_ms + 3_690_000));
+
+        // Times of day are on the day the recording starts, or the next one when earlier than the start
+        assert_eq!(parse_position("22:15:00", start_ms), Some(start_ms + 100_000));
+        assert_eq!(parse_position("22:15:00 UTC", start_ms), Some(start_ms + 100_000));
+        assert_eq!(parse_position("00:00:10", start_ms), Some(start_ms + 6_410_000));
+        assert_eq!(parse_position("24:00:00", start_ms), None);
+
+        assert_eq!(parse_position("2023-11-14T22:13:20Z", start_ms), Some(start_ms));
+        assert_eq!(parse_position("2023-11-14 23:13:20.5+01:00", start_ms), Some(start_ms + 500));
+        assert_eq!(parse_position("2023-11-14T20:13:20-02:00", start_ms), Some(start_ms));
+        assert_eq!(parse_position("2023-13-14T22:13:20Z", start_ms), None);
+        // Only digits after the decimal point
+        assert_eq!(parse_position("2023-11-14T22:13:20.ééZ", start_ms), None);
+        assert_eq!(parse_position("22:13:20.éé", start_ms), None);
+        assert_eq!(parse_position("2023-11-14T22:13:20.-5Z", start_ms), None);
+        assert_eq!(parse_position("1:x", start_ms), None);
+    }
+}
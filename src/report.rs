@@ -0,0 +1,250 @@
+use std::{io::{self, Write}, thread};
+use serde::Serialize;
+use crate::{
+    cli::SnapshotArgs,
+    metrics::MetricsSource,
+    settings::Settings,
+    state::rate_per_second,
+    sys_poller::{DiskData, NetworkData, ProcessData, Snapshot}
+};
+
+// JSON form of a single sample, printed by `sys_tui snapshot`.
+// Field names carry their unit so scripts don't have to guess
+#[derive(Serialize)]
+pub struct Report {
+    pub timestamp_ms: u64,
+    pub cpu: CpuReport,
+    pub memory: MemoryReport,
+    pub swap: MemoryReport,
+    pub disks: Vec<DiskReport>,
+    pub networks: Vec<NetworkReport>,
+    pub process_count: usize,
+    pub processes: Vec<ProcessReport>
+}
+
+#[derive(Serialize)]
+pub struct CpuReport {
+    pub brand: Option<String>,
+    pub core_count: Option<usize>,
+    pub usage_percent: f64,
+    pub frequency_mhz: Option<u64>,
+    pub cores: Vec<CoreReport>
+}
+
+#[derive(Serialize)]
+pub struct CoreReport {
+    pub usage_percent: f64,
+    pub frequency_mhz: Option<u64>
+}
+
+#[derive(Serialize)]
+pub struct MemoryReport {
+    pub free_mb: u64,
+    pub used_mb: u64,
+    pub total_mb: u64
+}
+
+#[derive(Serialize)]
+pub struct DiskReport {
+    pub name: String,
+    pub kind: String,
+    pub file_system: String,
+    pub mount_point: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    // Cumulative since boot, rates are since the previous sample
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+    pub read_bytes_per_second: Option<f64>,
+    pub written_bytes_per_second: Option<f64>
+}
+
+#[derive(Serialize)]
+pub struct NetworkReport {
+    pub name: String,
+    pub received_bytes: u64,
+    pub transmitted_bytes: u64,
+    pub received_packets: u64,
+    pub transmitted_packets: u64,
+    pub receive_errors: u64,
+    pub transmit_errors: u64,
+    pub received_bytes_per_second: Option<f64>,
+    pub transmitted_bytes_per_second: Option<f64>
+}
+
+#[derive(Serialize)]
+pub struct ProcessReport {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub cpu_usage_percent: f32,
+    pub memory_bytes: u64,
+    pub status: String,
+    pub command: String
+}
+
+impl Report {
+    // Rates are left out when there is no previous sample to compare with
+    pub fn new(snapshot: &Snapshot, previous: Option<&Snapshot>, top_processes: usize) -> Self {
+        let elapsed_s = previous
+            .map(|p| snapshot.timestamp_ms.saturating_sub(p.timestamp_ms) as f64 / 1000.0)
+            .filter(|elapsed_s| *elapsed_s > 0.0);
+
+        let cores = snapshot.per_core_usage.iter().enumerate().map(|(i, usage)| CoreReport {
+            usage_percent: *usage,
+            frequency_mhz: snapshot.per_core_frequency.get(i).copied()
+        }).collect();
+
+        let disks = snapshot.disks.iter().map(|d| {
+            let last = previous
+                .and_then(|p| p.disks.iter().find(|last| last.mount_point == d.mount_point));
+            disk_report(d, last, elapsed_s)
+        }).collect();
+
+        let networks = snapshot.networks.iter().map(|n| {
+            let last = previous
+                .and_then(|p| p.networks.iter().find(|last| last.name == n.name));
+            network_report(n, last, elapsed_s)
+        }).collect();
+
+        let mut processes: Vec<&ProcessData> = snapshot.processes.iter().collect();
+        processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage).then(a.pid.cmp(&b.pid)));
+        let processes = processes.into_iter().take(top_processes).map(process_report).collect();
+
+        Self {
+            timestamp_ms: snapshot.timestamp_ms,
+            cpu: CpuReport {
+                brand: snapshot.cpu_brand.clone(),
+                core_count: snapshot.core_count,
+                usage_percent: snapshot.avg_cpu_usage,
+                frequency_mhz: snapshot.avg_cpu_frequency,
+                cores
+            },
+            memory: memory_report(snapshot.memory),
+            swap: memory_report(snapshot.swap),
+            disks,
+            networks,
+            process_count: snapshot.processes.len(),
+            processes
+        }
+    }
+}
+
+fn memory_report((free_mb, used_mb, total_mb): (u64, u64, u64)) -> MemoryReport {
+    MemoryReport { free_mb, used_mb, total_mb }
+}
+
+fn disk_report(d: &DiskData, last: Option<&DiskData>, elapsed_s: Option<f64>) -> DiskReport {
+    let rate = |counter: fn(&DiskData) -> u64| last
+        .zip(elapsed_s)
+        .map(|(last, elapsed_s)| rate_per_second(counter(last), counter(d), elapsed_s));
+
+    DiskReport {
+        name: d.name.to_string_lossy().into_owned(),
+        kind: d.kind.to_string(),
+        file_system: d.file_system.to_string_lossy().into_owned(),
+        mount_point: d.mount_point.to_string_lossy().into_owned(),
+        total_bytes: d.total_space,
+        available_bytes: d.available_space,
+        read_bytes: d.read_bytes,
+        written_bytes: d.written_bytes,
+        read_bytes_per_second: rate(|d| d.read_bytes),
+        written_bytes_per_second: rate(|d| d.written_bytes)
+    }
+}
+
+fn network_report(n: &NetworkData, last: Option<&NetworkData>, elapsed_s: Option<f64>) -> NetworkReport {
+    let rate = |counter: fn(&NetworkData) -> u64| last
+        .zip(elapsed_s)
+        .map(|(last, elapsed_s)| rate_per_second(counter(last), counter(n), elapsed_s));
+
+    NetworkReport {
+        name: n.name.clone(),
+        received_bytes: n.total_received,
+        transmitted_bytes: n.total_transmitted,
+        received_packets: n.total_packets_received,
+        transmitted_packets: n.total_packets_transmitted,
+        receive_errors: n.total_errors_on_received,
+        transmit_errors: n.total_errors_on_transmitted,
+        received_bytes_per_second: rate(|n| n.total_received),
+        transmitted_bytes_per_second: rate(|n| n.total_transmitted)
+    }
+}
+
+fn process_report(p: &ProcessData) -> ProcessReport {
+    ProcessReport {
+        pid: p.pid,
+        name: p.name.clone(),
+        user: p.user.clone(),
+        cpu_usage_percent: p.cpu_usage,
+        memory_bytes: p.memory,
+        status: p.status.clone(),
+        command: p.command.clone()
+    }
+}
+
+// Headless counterpart of run_app: no raw mode, no alternate screen, just JSON on stdout.
+// A first sample is taken and thrown away so that CPU usage and the rates have something
+// to be computed against, every printed sample then comes one interval after the previous one
+pub fn print_snapshots<S: MetricsSource>(mut source: S, settings: &Settings, args: &SnapshotArgs) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    source.refresh();
+    let mut previous = source.snapshot();
+    for _ in 0..args.count {
+        thread::sleep(settings.sample_interval);
+        source.refresh();
+        let snapshot = source.snapshot();
+
+        let report = Report::new(&snapshot, Some(&previous), args.top);
+        if args.pretty {
+            serde_json::to_writer_pretty(&mut stdout, &report)?;
+        } else {
+            serde_json::to_writer(&mut stdout, &report)?;
+        }
+        writeln!(stdout)?;
+        stdout.flush()?;
+
+        previous = snapshot;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::fixtures::{network, process};
+
+    fn frame(timestamp_ms: u64, received: u64) -> Snapshot {
+        Snapshot {
+            timestamp_ms,
+            avg_cpu_usage: 12.5,
+            per_core_usage: vec![10.0, 15.0],
+            per_core_frequency: vec![2000],
+            networks: vec![network("eth0", received, 0)],
+            processes: (1..=5).map(|pid| process(pid, &format!("p{pid}"), pid as f32, 0)).collect(),
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn rates_are_computed_against_the_previous_sample() {
+        let report = Report::new(&frame(3000, 5000), Some(&frame(1000, 1000)), 10);
+        assert_eq!(report.networks[0].received_bytes_per_second, Some(2000.0));
+
+        let first = Report::new(&frame(1000, 1000), None, 10);
+        assert_eq!(first.networks[0].received_bytes_per_second, None);
+    }
+
+    #[test]
+    fn only_the_busiest_processes_are_listed() {
+        let report = Report::new(&frame(1000, 0), None, 2);
+        assert_eq!(report.process_count, 5);
+        assert_eq!(report.processes.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![5, 4]);
+        assert_eq!(report.cpu.cores[1].frequency_mhz, None);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["cpu"]["usage_percent"], 12.5);
+        assert_eq!(json["processes"][0]["name"], "p5");
+    }
+}
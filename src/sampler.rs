@@ -0,0 +1,83 @@
+use std::{
+    io,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant}
+};
+use sysinfo::Signal;
+use crate::{metrics::MetricsSource, sys_poller::Snapshot};
+
+// Requests from the UI to the sampler thread, which owns the metrics source
+pub enum SamplerCommand {
+    SendSignal { pid: u32, name: String, signal: Signal },
+    SetInterval(Duration)
+}
+
+// Everything the sampler thread sends back to the UI
+pub enum SamplerMessage {
+    Snapshot(Box<Snapshot>),
+    SignalResult { pid: u32, name: String, signal: Signal, result: io::Result<()> }
+}
+
+// Handle to the sampler thread. The thread stops once this is dropped
+pub struct Sampler {
+    messages: Receiver<SamplerMessage>,
+    commands: Sender<SamplerCommand>
+}
+
+impl Sampler {
+    pub fn spawn<S: MetricsSource + Send + 'static>(source: S, interval: Duration) -> Self {
+        let (message_sender, messages) = mpsc::channel();
+        let (commands, command_receiver) = mpsc::channel();
+        thread::spawn(move || run(source, interval, command_receiver, message_sender));
+        Self { messages, commands }
+    }
+
+    pub fn send(&self, command: SamplerCommand) {
+        // The thread only goes away with this handle, so there is nothing to do on error
+        let _ = self.commands.send(command);
+    }
+
+    // Next pending message, if any. Never blocks
+    pub fn try_recv(&self) -> Option<SamplerMessage> {
+        self.messages.try_recv().ok()
+    }
+
+    // Waits for the next message. Disconnected means the thread is gone, it panicked
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<SamplerMessage, RecvTimeoutError> {
+        self.messages.recv_timeout(timeout)
+    }
+}
+
+// Sample at a fixed interval, handling commands as they come in between samples
+fn run<S: MetricsSource>(mut source: S, mut interval: Duration, commands: Receiver<SamplerCommand>, messages: Sender<SamplerMessage>) {
+    let mut next_sample = Instant::now();
+    loop {
+        let message = match commands.recv_timeout(next_sample.saturating_duration_since(Instant::now())) {
+            Ok(SamplerCommand::SendSignal { pid, name, signal }) => {
+                let result = source.send_signal(pid, signal);
+                SamplerMessage::SignalResult { pid, name, signal, result }
+            },
+            Ok(SamplerCommand::SetInterval(new_interval)) => {
+                // Reschedule the pending sample as if it had been planned with the new interval
+                next_sample = (next_sample + new_interval).checked_sub(interval).unwrap_or_else(Instant::now);
+                interval = new_interval;
+                continue;
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                source.refresh();
+                next_sample += interval;
+                // Don't try to catch up on missed samples, e.g. after the machine was suspended
+                if next_sample < Instant::now() {
+                    next_sample = Instant::now() + interval;
+                }
+                SamplerMessage::Snapshot(Box::new(source.snapshot()))
+            },
+            Err(RecvTimeoutError::Disconnected) => return
+        };
+
+        if messages.send(message).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+// sysinfo needs at least this long between two refreshes to compute CPU usage
+pub const MIN_SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+pub const MAX_SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+pub const MIN_HISTORY: Duration = Duration::from_secs(5);
+pub const MAX_HISTORY: Duration = Duration::from_secs(3600);
+
+// Timing settings shared by the sampler, every dataset and every graph axis
+#[derive(Clone, Copy)]
+pub struct Settings {
+    // Time between two samples taken by the sampler thread
+    pub sample_interval: Duration,
+    // How far back the datasets keep samples and the graphs show them
+    pub history: Duration
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            sample_interval: Duration::from_millis(500),
+            history: Duration::from_secs(25)
+        }
+    }
+}
+
+impl Settings {
+    pub fn history_ms(&self) -> f64 {
+        self.history.as_millis() as f64
+    }
+
+    // Runtime adjustments double or halve the current value, within bounds
+    pub fn increase_sample_interval(&mut self) {
+        self.sample_interval = (self.sample_interval * 2).min(MAX_SAMPLE_INTERVAL);
+    }
+
+    pub fn decrease_sample_interval(&mut self) {
+        self.sample_interval = (self.sample_interval / 2).max(MIN_SAMPLE_INTERVAL);
+    }
+
+    pub fn increase_history(&mut self) {
+        self.history = (self.history * 2).min(MAX_HISTORY);
+    }
+
+    pub fn decrease_history(&mut self) {
+        self.history = (self.history / 2).max(MIN_HISTORY);
+    }
+}
@@ -2,57 +2,453 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
-use std::collections::VecDeque;
+use std::{collections::{HashMap, VecDeque}, path::PathBuf, time::Instant};
 
 use ratatui::{
-    widgets::{Dataset, GraphType},
+    layout::Rect,
+    widgets::{Dataset, GraphType, TableState},
     style::{Style, Color}
 };
-use std::time::Instant;
-use crate::sys_poller;
+use std::cmp::Ordering;
+use serde::Deserialize;
+use sysinfo::{Signal, SUPPORTED_SIGNALS};
+use crate::{
+    alerts::Alerts,
+    config::Config,
+    export,
+    metrics::MetricsSource,
+    keymap::{Action, KeyMap},
+    recorder::Recorder,
+    replay::{self, Replay, SEEK_STEP},
+    settings::{MAX_HISTORY, MIN_SAMPLE_INTERVAL},
+    sampler::{Sampler, SamplerCommand, SamplerMessage},
+    settings::Settings,
+    sys_poller::{self, DiskData, NetworkData, ProcessData, Snapshot},
+    theme::Theme
+};
 
 
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Graph {
     CPU,
     MEMORY,
-    DISK
+    DISK,
+    NETWORK,
+    PROCESS,
+    ALERTS
+}
+
+// Panels that can be focused by clicking on them
+#[derive(Clone, Copy, PartialEq)]
+pub enum Panel {
+    Cpu,
+    Memory,
+    Disks,
+    Network,
+    Graph
+}
+
+impl Panel {
+    pub const ALL: [Panel; 5] = [Panel::Cpu, Panel::Memory, Panel::Disks, Panel::Network, Panel::Graph];
+
+    // Key of the panel in the areas map built by the UI
+    pub fn area_name(self) -> &'static str {
+        match self {
+            Panel::Cpu => "cpu_info",
+            Panel::Memory => "mem_info",
+            Panel::Disks => "disk_info",
+            Panel::Network => "net_info",
+            Panel::Graph => "graph_area"
+        }
+    }
 }
 
 pub struct State {
-    pub system: sys_poller::SysInfo,
+    // None while replaying a recording, snapshots then come from the replay
+    sampler: Option<Sampler>,
+    pub replay: Option<Replay>,
+    pub settings: Settings,
+    pub theme: Theme,
+    pub keymap: KeyMap,
+    // Themes the user can cycle through, the configured one included
+    themes: Vec<Theme>,
+    // Latest snapshot received from the sampler thread
+    pub snapshot: Snapshot,
     pub graph: Graph,
     pub cpu_dataset: CpuDataset,
-    start_time: Instant,
-    pub graph_size_percentage: u16
+    // Draw one line per core on the CPU graph instead of the aggregate
+    pub cpu_per_core: bool,
+    // Plot the frequency history under the usage on the CPU graph
+    pub cpu_show_frequency: bool,
+    pub memory_dataset: MemoryDataset,
+    pub disk_dataset: DiskDataset,
+    pub network_dataset: NetworkDataset,
+    pub process_view: ProcessView,
+    pub signal_dialog: Option<SignalDialog>,
+    // First row shown by the key list overlay, None while it is closed
+    pub help_offset: Option<usize>,
+    // Feedback for the last user action, shown at the bottom of the screen
+    pub status_message: Option<String>,
+    // Timestamp of the first snapshot, datasets are relative to it
+    start_timestamp_ms: Option<u64>,
+    // Time of the latest snapshot relative to the first one
+    pub elapsed_ms: f64,
+    pub graph_size_percentage: u16,
+    // Where every panel was drawn on the last frame, used to tell what the mouse is over
+    pub areas: HashMap<String, Rect>,
+    // Frames drawn so far, what blinks on screen goes by it
+    pub frame_count: u64,
+    pub focused: Option<Panel>,
+    // The border between the info column and the graph is being dragged
+    pub resizing_graph: bool,
+    // Index of the first disk shown in the disk table
+    pub disk_table_offset: usize,
+    // Every snapshot is appended to the recording while there is one
+    recorder: Option<Recorder>,
+    // Where the CSV export key writes to
+    export_dir: PathBuf,
+    pub alerts: Alerts
 }
 
 impl State {
-    pub fn new(sys: sys_poller::SysInfo) -> Self {
+    // Sampling starts right away on a background thread
+    pub fn new<S: MetricsSource + Send + 'static>(source: S, config: &Config) -> Self {
+        Self::with_feed(Some(Sampler::spawn(source, config.settings.sample_interval)), None, config)
+    }
+
+    // Plays back a recording instead of sampling this machine
+    pub fn new_replay(replay: Replay, config: &Config) -> Self {
+        Self::with_feed(None, Some(replay), config)
+    }
+
+    fn with_feed(sampler: Option<Sampler>, replay: Option<Replay>, config: &Config) -> Self {
+        // A recording is not what this machine is doing now, no command is run for it
+        let alerts = Alerts::new(config.alerts.clone(), replay.is_none());
         Self {
-            system: sys,
-            graph: Graph::CPU,
+            sampler,
+            replay,
+            settings: config.settings,
+            theme: config.theme.clone(),
+            themes: theme_cycle(&config.theme),
+            keymap: config.keymap.clone(),
+            snapshot: Snapshot::default(),
+            graph: config.default_graph,
             cpu_dataset: CpuDataset::new(),
-            start_time: Instant::now(),
-            graph_size_percentage: 60
+            cpu_per_core: false,
+            cpu_show_frequency: false,
+            memory_dataset: MemoryDataset::new(),
+            disk_dataset: DiskDataset::new(),
+            network_dataset: NetworkDataset::new(),
+            process_view: ProcessView::new(),
+            signal_dialog: None,
+            help_offset: None,
+            status_message: None,
+            start_timestamp_ms: None,
+            elapsed_ms: 0.0,
+            graph_size_percentage: config.graph_size_percentage,
+            areas: HashMap::new(),
+            frame_count: 0,
+            focused: None,
+            resizing_graph: false,
+            disk_table_offset: 0,
+            recorder: None,
+            export_dir: config.export_dir.clone(),
+            alerts
+        }
+    }
+
+    // Run an action the user triggered. Quitting is left to the caller
+    pub fn dispatch(&mut self, action: Action) {
+        // The list keys scroll the alert log while it is shown
+        if action.is_process_action() && matches!(self.graph, Graph::ALERTS) {
+            self.scroll_alert_log(action);
+            return;
+        }
+        if action.is_process_action() && !matches!(self.graph, Graph::PROCESS) {
+            return;
+        }
+        if action.is_replay_action() && self.replay.is_none() {
+            return;
+        }
+        match action {
+            Action::Quit => {},
+            Action::ToggleHelp => self.toggle_help(),
+            Action::ShowCpuGraph => self.set_graph_cpu(),
+            Action::TogglePerCore => self.toggle_cpu_per_core(),
+            Action::ToggleFrequency => self.toggle_cpu_frequency(),
+            Action::ShowMemoryGraph => self.set_graph_memory(),
+            Action::ShowDiskGraph => self.set_graph_disk(),
+            Action::ShowNetworkGraph => self.set_graph_network(),
+            Action::ShowProcesses => self.set_graph_process(),
+            Action::ShowAlerts => self.set_graph_alerts(),
+            Action::NextDisk => self.cycle_highlighted_disk(),
+            Action::ExpandGraph => self.expand_graph_size(),
+            Action::ReduceGraph => self.reduce_graph_size(),
+            Action::IncreaseInterval => self.increase_sample_interval(),
+            Action::DecreaseInterval => self.decrease_sample_interval(),
+            Action::IncreaseHistory => self.increase_history(),
+            Action::DecreaseHistory => self.decrease_history(),
+            Action::CycleTheme => self.cycle_theme(),
+            Action::ExportCsv => self.export_csv(),
+            Action::SelectUp => self.process_view.move_selection(-1),
+            Action::SelectDown => self.process_view.move_selection(1),
+            Action::PageUp => self.process_view.move_selection(-10),
+            Action::PageDown => self.process_view.move_selection(10),
+            Action::SelectFirst => self.process_view.select_first(),
+            Action::SelectLast => self.process_view.select_last(),
+            Action::PreviousSortColumn => self.process_view.previous_sort_column(),
+            Action::NextSortColumn => self.process_view.next_sort_column(),
+            Action::ReverseSort => self.process_view.reverse_sort(),
+            Action::Filter => self.process_view.start_filtering(),
+            Action::SendSignal => self.open_signal_dialog(),
+            Action::TogglePlayback => self.toggle_playback(),
+            Action::SlowerPlayback => self.change_playback_speed(Replay::slower),
+            Action::FasterPlayback => self.change_playback_speed(Replay::faster),
+            Action::SeekBackward => self.seek_replay(-(SEEK_STEP.as_millis() as i64)),
+            Action::SeekForward => self.seek_replay(SEEK_STEP.as_millis() as i64),
+            Action::JumpTo => self.start_replay_jump()
         }
     }
 
+    // Take in whatever the sampler thread sent since the last call.
+    // Cheap enough to call on every frame
     pub fn refresh(&mut self) -> f64 {
-        self.system.refresh();
-        let elapsed_ms = self.refresh_cpu_dataset();
-        elapsed_ms
+        while let Some(message) = self.sampler.as_ref().and_then(Sampler::try_recv) {
+            self.handle_message(message);
+        }
+        if let Some(replay) = &mut self.replay {
+            let snapshots = replay.advance(Instant::now()).to_vec();
+            for snapshot in snapshots {
+                self.apply_snapshot(snapshot);
+            }
+        }
+        self.elapsed_ms
+    }
+
+    // Tests wait for the sampler thread instead of polling it, so they see an exact number of messages
+    #[cfg(test)]
+    pub fn wait_for_messages(&mut self, count: usize) -> f64 {
+        for _ in 0..count {
+            let message = self.sampler.as_ref()
+                .and_then(|sampler| sampler.recv_timeout(std::time::Duration::from_secs(5)).ok())
+                .expect("sampler thread sent nothing");
+            self.handle_message(message);
+        }
+        self.elapsed_ms
+    }
+
+    fn handle_message(&mut self, message: SamplerMessage) {
+        match message {
+            SamplerMessage::Snapshot(snapshot) => self.apply_snapshot(*snapshot),
+            SamplerMessage::SignalResult { pid, name, signal, result } => {
+                let signal_name = sys_poller::signal_name(signal);
+                self.status_message = Some(match result {
+                    Ok(()) => format!("Sent {signal_name} to {pid} ({name})"),
+                    Err(e) => format!("Failed to send {signal_name} to {pid} ({name}): {e}")
+                });
+            }
+        }
+    }
+
+    pub fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        let start_timestamp_ms = *self.start_timestamp_ms.get_or_insert(snapshot.timestamp_ms);
+        let elapsed_ms = snapshot.timestamp_ms.saturating_sub(start_timestamp_ms) as f64;
+
+        self.snapshot = snapshot;
+        self.elapsed_ms = elapsed_ms;
+        self.refresh_cpu_dataset(elapsed_ms);
+        self.refresh_memory_dataset(elapsed_ms);
+        self.refresh_disk_dataset(elapsed_ms);
+        self.refresh_network_dataset(elapsed_ms);
+        self.process_view.update_processes(self.snapshot.processes.clone());
+        if let Some(message) = self.alerts.evaluate(&self.snapshot) {
+            self.status_message = Some(message);
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.write(&self.snapshot) {
+                // Don't retry on every sample, e.g. when the disk is full
+                self.status_message = Some(format!("Recording to {} stopped: {e}", recorder.path().display()));
+                self.recorder = None;
+            }
+        }
+    }
+
+    pub fn start_recording(&mut self, recorder: Recorder) {
+        self.status_message = Some(format!("Recording to {}", recorder.path().display()));
+        self.recorder = Some(recorder);
+    }
+
+    pub fn toggle_playback(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        // Playing again once the end was reached starts over
+        if !replay.playing && replay.at_end() {
+            let snapshots = replay.seek(replay.start_ms(), self.settings.history).to_vec();
+            self.restart_datasets(snapshots);
+        }
+        if let Some(replay) = &mut self.replay {
+            replay.toggle_playing();
+        }
+    }
+
+    fn change_playback_speed(&mut self, change: fn(&mut Replay)) {
+        if let Some(replay) = &mut self.replay {
+            change(replay);
+            self.status_message = Some(format!("Playback speed: {}x", replay.speed()));
+        }
+    }
+
+    pub fn seek_replay(&mut self, offset_ms: i64) {
+        if let Some(replay) = &mut self.replay {
+            let snapshots = replay.seek_by(offset_ms, self.settings.history).to_vec();
+            self.restart_datasets(snapshots);
+        }
+    }
+
+    pub fn start_replay_jump(&mut self) {
+        if let Some(replay) = &mut self.replay {
+            replay.jump_input = Some(String::new());
+        }
+    }
+
+    // Go to the position typed after the jump key, an offset or a time within the recording
+    pub fn confirm_replay_jump(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        let Some(input) = replay.jump_input.take() else {
+            return;
+        };
+        match replay::parse_position(&input, replay.start_ms()) {
+            Some(position_ms) => {
+                let snapshots = replay.seek(position_ms, self.settings.history).to_vec();
+                self.restart_datasets(snapshots);
+            },
+            None => self.status_message = Some(format!(
+                "Not a position, expected seconds, m:ss, +h:mm:ss, a UTC time hh:mm:ss or an RFC 3339 time: {input}"
+            ))
+        }
+    }
+
+    // Called before drawing, see TimeSeries
+    pub fn make_series_contiguous(&mut self) {
+        self.cpu_dataset.cpu_usage.make_contiguous();
+        for series in &mut self.cpu_dataset.per_core_usage {
+            series.make_contiguous();
+        }
+        self.cpu_dataset.frequency.make_contiguous();
+        self.memory_dataset.used_memory.make_contiguous();
+        self.memory_dataset.free_memory.make_contiguous();
+        self.memory_dataset.used_swap.make_contiguous();
+        for disk in &mut self.disk_dataset.disks {
+            disk.read_rate.make_contiguous();
+            disk.write_rate.make_contiguous();
+            disk.used_space.make_contiguous();
+        }
+        for interface in &mut self.network_dataset.interfaces {
+            interface.rx_rate.make_contiguous();
+            interface.tx_rate.make_contiguous();
+        }
+    }
+
+    // Every series the graphs are drawn from, as far back as the history window goes
+    pub fn export_csv(&mut self) {
+        let Some(start_timestamp_ms) = self.start_timestamp_ms else {
+            self.status_message = Some("Nothing to export yet".to_string());
+            return;
+        };
+
+        let mut columns: Vec<(String, &TimeSeries)> = vec![("cpu usage %".to_string(), &self.cpu_dataset.cpu_usage)];
+        for (core, series) in self.cpu_dataset.per_core_usage.iter().enumerate() {
+            columns.push((format!("core {core} usage %"), series));
+        }
+        columns.push(("cpu frequency MHz".to_string(), &self.cpu_dataset.frequency));
+        columns.push(("memory used MB".to_string(), &self.memory_dataset.used_memory));
+        columns.push(("memory free MB".to_string(), &self.memory_dataset.free_memory));
+        columns.push(("swap used MB".to_string(), &self.memory_dataset.used_swap));
+        for disk in &self.disk_dataset.disks {
+            columns.push((format!("{} read MB/s", disk.mount_point), &disk.read_rate));
+            columns.push((format!("{} write MB/s", disk.mount_point), &disk.write_rate));
+            columns.push((format!("{} used %", disk.mount_point), &disk.used_space));
+        }
+        for interface in &self.network_dataset.interfaces {
+            columns.push((format!("{} rx B/s", interface.data.name), &interface.rx_rate));
+            columns.push((format!("{} tx B/s", interface.data.name), &interface.tx_rate));
+        }
+
+        let csv = export::to_csv(start_timestamp_ms, &columns);
+        self.status_message = Some(match export::write_csv(&self.export_dir, self.snapshot.timestamp_ms, &csv) {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(e) => format!("Export to {} failed: {e}", self.export_dir.display())
+        });
+    }
+
+    // After a seek the graphs are rebuilt from scratch, as they would have looked at that point
+    fn restart_datasets(&mut self, snapshots: Vec<Snapshot>) {
+        self.cpu_dataset = CpuDataset::new();
+        self.memory_dataset = MemoryDataset::new();
+        self.disk_dataset = DiskDataset::new();
+        self.network_dataset = NetworkDataset::new();
+        self.alerts.reset();
+        for snapshot in snapshots {
+            self.apply_snapshot(snapshot);
+        }
     }
 
-    pub fn refresh_cpu_dataset(&mut self) -> f64 {
-        
+    pub fn refresh_cpu_dataset(&mut self, elapsed_ms: f64) {
         // Refresh cpu usage
-        let elapsed_ms = self.start_time.elapsed().as_millis() as f64;
+        let history_ms = self.settings.history_ms();
         self.cpu_dataset.update_cpu_usage(
             elapsed_ms,
-            self.system.get_avg_cpu_usage().into()
-        );        
-        
-        return elapsed_ms;
+            history_ms,
+            self.snapshot.avg_cpu_usage
+        );
+        self.cpu_dataset.update_per_core_usage(
+            elapsed_ms,
+            history_ms,
+            &self.snapshot.per_core_usage
+        );
+        self.cpu_dataset.update_frequency(
+            elapsed_ms,
+            history_ms,
+            &self.snapshot.per_core_frequency
+        );
+    }
+
+    pub fn refresh_memory_dataset(&mut self, elapsed_ms: f64) {
+        // Refresh RAM and swap usage
+        self.memory_dataset.update_memory_usage(
+            elapsed_ms,
+            self.settings.history_ms(),
+            self.snapshot.memory,
+            self.snapshot.swap
+        );
+    }
+
+    pub fn refresh_disk_dataset(&mut self, elapsed_ms: f64) {
+        // Refresh disk throughput and used capacity
+        self.disk_dataset.update_disk_usage(
+            elapsed_ms,
+            self.settings.history_ms(),
+            &self.snapshot.disks
+        );
+    }
+
+    pub fn refresh_network_dataset(&mut self, elapsed_ms: f64) {
+        // Refresh rx/tx throughput of every interface
+        self.network_dataset.update_network_usage(
+            elapsed_ms,
+            self.settings.history_ms(),
+            self.snapshot.networks.clone()
+        );
+    }
+
+    pub fn set_graph(&mut self, graph: Graph) {
+        self.graph = graph
     }
 
     pub fn set_graph_cpu(&mut self) {
@@ -67,6 +463,125 @@ impl State {
         self.graph = Graph::DISK
     }
 
+    pub fn set_graph_network(&mut self) {
+        self.graph = Graph::NETWORK
+    }
+
+    pub fn set_graph_process(&mut self) {
+        self.graph = Graph::PROCESS
+    }
+
+    pub fn set_graph_alerts(&mut self) {
+        self.graph = Graph::ALERTS
+    }
+
+    // Newest entries are on top, the way up leads back to them
+    fn scroll_alert_log(&mut self, action: Action) {
+        match action {
+            Action::SelectUp => self.alerts.scroll_log(-1),
+            Action::SelectDown => self.alerts.scroll_log(1),
+            Action::PageUp => self.alerts.scroll_log(-10),
+            Action::PageDown => self.alerts.scroll_log(10),
+            Action::SelectFirst => self.alerts.log_offset = 0,
+            Action::SelectLast => self.alerts.scroll_log(isize::MAX),
+            _ => {}
+        }
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.help_offset = match self.help_offset {
+            Some(_) => None,
+            None => Some(0)
+        };
+    }
+
+    // The overlay clamps the offset when it is drawn, it knows how many rows fit
+    pub fn scroll_help(&mut self, delta: isize) {
+        if let Some(offset) = &mut self.help_offset {
+            *offset = offset.saturating_add_signed(delta);
+        }
+    }
+
+    // Ask which signal to send to the selected process.
+    // A recorded pid may belong to another process by now, or to none
+    pub fn open_signal_dialog(&mut self) {
+        if self.replay.is_some() {
+            self.status_message = Some("Signals unavailable during replay".to_string());
+            return;
+        }
+        if let Some(process) = self.process_view.selected_process() {
+            self.signal_dialog = Some(SignalDialog::new(process.pid, process.name.clone()));
+        }
+    }
+
+    pub fn close_signal_dialog(&mut self) {
+        self.signal_dialog = None;
+    }
+
+    // The sampler thread sends the signal, its result shows up on a later refresh
+    pub fn confirm_signal_dialog(&mut self) {
+        let Some(dialog) = self.signal_dialog.take() else {
+            return;
+        };
+        let Some(signal) = dialog.selected_signal() else {
+            return;
+        };
+
+        if let Some(sampler) = &self.sampler {
+            sampler.send(SamplerCommand::SendSignal {
+                pid: dialog.pid,
+                name: dialog.name,
+                signal
+            });
+        }
+    }
+
+    pub fn toggle_cpu_per_core(&mut self) {
+        self.cpu_per_core = !self.cpu_per_core;
+    }
+
+    pub fn toggle_cpu_frequency(&mut self) {
+        self.cpu_show_frequency = !self.cpu_show_frequency;
+    }
+
+    pub fn cycle_highlighted_disk(&mut self) {
+        self.disk_dataset.cycle_highlighted();
+    }
+
+    pub fn increase_sample_interval(&mut self) {
+        self.settings.increase_sample_interval();
+        self.apply_sample_interval();
+    }
+
+    pub fn decrease_sample_interval(&mut self) {
+        self.settings.decrease_sample_interval();
+        self.apply_sample_interval();
+    }
+
+    fn apply_sample_interval(&mut self) {
+        if let Some(sampler) = &self.sampler {
+            sampler.send(SamplerCommand::SetInterval(self.settings.sample_interval));
+        }
+        self.status_message = Some(format!("Sample interval: {} ms", self.settings.sample_interval.as_millis()));
+    }
+
+    // Shorter windows take effect on the next sample, when datasets drop what fell out
+    pub fn increase_history(&mut self) {
+        self.settings.increase_history();
+        self.status_message = Some(format!("History: {} s", self.settings.history.as_secs()));
+    }
+
+    pub fn decrease_history(&mut self) {
+        self.settings.decrease_history();
+        self.status_message = Some(format!("History: {} s", self.settings.history.as_secs()));
+    }
+
+    pub fn cycle_theme(&mut self) {
+        let current = self.themes.iter().position(|theme| theme.name == self.theme.name).unwrap_or(0);
+        self.theme = self.themes[(current + 1) % self.themes.len()].clone();
+        self.status_message = Some(format!("Theme: {}", self.theme.name));
+    }
+
     pub fn expand_graph_size(&mut self) {
         self.graph_size_percentage += 2;
         if self.graph_size_percentage >= 100 {
@@ -76,41 +591,751 @@ impl State {
 
     pub fn reduce_graph_size(&mut self) {
         // Avoid assigning a u16 value as negative
-        if self.graph_size_percentage <= 0 {
-            self.graph_size_percentage = 0;
-        } else {
-            self.graph_size_percentage -= 2;
+        self.graph_size_percentage = self.graph_size_percentage.saturating_sub(2);
+    }
+
+    pub fn set_graph_size_percentage(&mut self, percentage: u16) {
+        self.graph_size_percentage = percentage.min(100);
+    }
+
+    // Focusing an info panel also shows its graph
+    pub fn focus_panel(&mut self, panel: Panel) {
+        self.focused = Some(panel);
+        match panel {
+            Panel::Cpu => self.set_graph_cpu(),
+            Panel::Memory => self.set_graph_memory(),
+            Panel::Disks => self.set_graph_disk(),
+            Panel::Network => self.set_graph_network(),
+            Panel::Graph => {}
+        }
+    }
+
+    // The last disk always stays visible
+    pub fn scroll_disk_table(&mut self, delta: isize) {
+        let max_offset = self.snapshot.disks.len().saturating_sub(1);
+        self.disk_table_offset = self.disk_table_offset.saturating_add_signed(delta).min(max_offset);
+    }
+}
+
+// Every preset, with a custom theme from the config file going first
+fn theme_cycle(configured: &Theme) -> Vec<Theme> {
+    let mut themes = Theme::presets();
+    if !themes.iter().any(|theme| theme.name == configured.name) {
+        themes.insert(0, configured.clone());
+    }
+    themes
+}
+
+// Most samples a TimeSeries holds: the longest history at the fastest sample rate
+const MAX_SAMPLES: usize = (MAX_HISTORY.as_millis() / MIN_SAMPLE_INTERVAL.as_millis()) as usize;
+
+// Ring buffer of (elapsed_ms, value) samples used by every dataset.
+// Samples older than the history window are evicted on every push, and the buffer
+// never grows past MAX_SAMPLES whatever the sample rate.
+// The graphs borrow it as a plain slice, so it is made contiguous once per frame before drawing
+pub struct TimeSeries {
+    samples: VecDeque<(f64, f64)>
+}
+
+impl TimeSeries {
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::new()
+        }
+    }
+
+    pub fn push(&mut self, elapsed_ms: f64, history_ms: f64, value: f64) {
+        while self.samples.front().is_some_and(|(t, _)| elapsed_ms - t > history_ms) {
+            self.samples.pop_front();
         }
+        if self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((elapsed_ms, value));
+    }
+
+    // Only moves samples around when the buffer has wrapped since the last call
+    pub fn make_contiguous(&mut self) {
+        self.samples.make_contiguous();
+    }
+
+    // Every sample, provided make_contiguous was called after the last push
+    pub fn as_slice(&self) -> &[(f64, f64)] {
+        let (samples, wrapped) = self.samples.as_slices();
+        debug_assert!(wrapped.is_empty(), "TimeSeries drawn without make_contiguous");
+        samples
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(f64, f64)> {
+        self.samples.iter()
+    }
+
+    pub fn last(&self) -> Option<f64> {
+        self.samples.back().map(|(_, v)| *v)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.samples.iter().map(|(_, v)| *v).reduce(f64::max)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
     }
 }
 
 pub struct CpuDataset {
-    pub cpu_usage: VecDeque<(f64, f64)>
+    pub cpu_usage: TimeSeries,
+    // One series per logical core, indexed like sysinfo's cpus()
+    pub per_core_usage: Vec<TimeSeries>,
+    // Average frequency across cores, in MHz
+    pub frequency: TimeSeries,
+    // Latest frequency of every core, in MHz
+    pub per_core_frequency: Vec<u64>,
+    // Lowest and highest frequency any core reported this session, in MHz
+    pub min_frequency: Option<u64>,
+    pub max_frequency: Option<u64>
 }
 
 impl CpuDataset {
     pub fn new() -> Self {
         Self {
-            cpu_usage: VecDeque::with_capacity(100000),
+            cpu_usage: TimeSeries::new(),
+            per_core_usage: Vec::new(),
+            frequency: TimeSeries::new(),
+            per_core_frequency: Vec::new(),
+            min_frequency: None,
+            max_frequency: None
         }
     }
 
     // Update vec and insert values
-    pub fn update_cpu_usage(&mut self, elapsed_ms: f64, value: f64) {
-        if elapsed_ms >= 25000.0 {
-            self.cpu_usage.pop_front();
+    pub fn update_cpu_usage(&mut self, elapsed_ms: f64, history_ms: f64, value: f64) {
+        self.cpu_usage.push(elapsed_ms, history_ms, value);
+    }
+
+    pub fn update_frequency(&mut self, elapsed_ms: f64, history_ms: f64, per_core_frequency: &[u64]) {
+        // Some VMs report 0 MHz, which would only drag the minimum down
+        for frequency in per_core_frequency.iter().copied().filter(|f| *f > 0) {
+            self.min_frequency = Some(self.min_frequency.map_or(frequency, |min| min.min(frequency)));
+            self.max_frequency = Some(self.max_frequency.map_or(frequency, |max| max.max(frequency)));
+        }
+        if !per_core_frequency.is_empty() {
+            let avg_frequency = per_core_frequency.iter().sum::<u64>() as f64 / per_core_frequency.len() as f64;
+            self.frequency.push(elapsed_ms, history_ms, avg_frequency);
+        }
+        self.per_core_frequency = per_core_frequency.to_vec();
+    }
+
+    pub fn avg_frequency(&self) -> Option<f64> {
+        self.frequency.last()
+    }
+
+    pub fn update_per_core_usage(&mut self, elapsed_ms: f64, history_ms: f64, values: &[f64]) {
+        self.per_core_usage.resize_with(values.len(), TimeSeries::new);
+        for (series, value) in self.per_core_usage.iter_mut().zip(values) {
+            series.push(elapsed_ms, history_ms, *value);
+        }
+    }
+}
+
+pub struct MemoryDataset {
+    pub used_memory: TimeSeries,
+    pub free_memory: TimeSeries,
+    pub used_swap: TimeSeries,
+    pub total_memory: u64,
+    pub total_swap: u64
+}
+
+impl MemoryDataset {
+    pub fn new() -> Self {
+        Self {
+            used_memory: TimeSeries::new(),
+            free_memory: TimeSeries::new(),
+            used_swap: TimeSeries::new(),
+            total_memory: 0,
+            total_swap: 0
+        }
+    }
+
+    // Insert RAM and swap values (in MB), dropping anything older than the window
+    pub fn update_memory_usage(&mut self, elapsed_ms: f64, history_ms: f64, memory: (u64, u64, u64), swap: (u64, u64, u64)) {
+        let (free_memory, used_memory, total_memory) = memory;
+        let (_, used_swap, total_swap) = swap;
+        self.total_memory = total_memory;
+        self.total_swap = total_swap;
+
+        for (series, value) in [
+            (&mut self.used_memory, used_memory),
+            (&mut self.free_memory, free_memory),
+            (&mut self.used_swap, used_swap)
+        ] {
+            series.push(elapsed_ms, history_ms, value as f64);
+        }
+    }
+}
+
+// Per second rate of a cumulative counter between two samples
+pub fn rate_per_second(previous: u64, current: u64, elapsed_s: f64) -> f64 {
+    current.saturating_sub(previous) as f64 / elapsed_s
+}
+
+pub struct DiskHistory {
+    pub name: String,
+    pub mount_point: String,
+    pub read_rate: TimeSeries,
+    pub write_rate: TimeSeries,
+    pub used_space: TimeSeries,
+    // (elapsed_ms, read_bytes, written_bytes) of the previous sample, to compute rates
+    last_io: Option<(f64, u64, u64)>
+}
+
+impl DiskHistory {
+    fn new(name: String, mount_point: String) -> Self {
+        Self {
+            name,
+            mount_point,
+            read_rate: TimeSeries::new(),
+            write_rate: TimeSeries::new(),
+            used_space: TimeSeries::new(),
+            last_io: None
+        }
+    }
+}
+
+pub struct DiskDataset {
+    // One entry per mount point, in the order sysinfo lists them
+    pub disks: Vec<DiskHistory>,
+    pub highlighted: usize
+}
+
+impl DiskDataset {
+    pub fn new() -> Self {
+        Self {
+            disks: Vec::new(),
+            highlighted: 0
+        }
+    }
+
+    // Insert read/write throughput (MB/s) and used capacity (%) for every disk
+    pub fn update_disk_usage(&mut self, elapsed_ms: f64, history_ms: f64, disks_data: &[DiskData]) {
+        let mut disks: Vec<DiskHistory> = Vec::with_capacity(disks_data.len());
+
+        for d in disks_data {
+            let mount_point = d.mount_point.to_string_lossy().into_owned();
+
+            // Keep the history of disks we already know about, start fresh for new ones
+            let mut history = match self.disks.iter().position(|h| h.mount_point == mount_point) {
+                Some(i) => self.disks.swap_remove(i),
+                None => DiskHistory::new(d.name.to_string_lossy().into_owned(), mount_point)
+            };
+
+            if let Some((last_ms, last_read, last_written)) = history.last_io {
+                let elapsed_s = (elapsed_ms - last_ms) / 1000.0;
+                if elapsed_s > 0.0 {
+                    let read_rate = rate_per_second(last_read, d.read_bytes, elapsed_s) / 1_000_000.0;
+                    let write_rate = rate_per_second(last_written, d.written_bytes, elapsed_s) / 1_000_000.0;
+                    history.read_rate.push(elapsed_ms, history_ms, read_rate);
+                    history.write_rate.push(elapsed_ms, history_ms, write_rate);
+                }
+            }
+            history.last_io = Some((elapsed_ms, d.read_bytes, d.written_bytes));
+
+            let used_percentage = if d.total_space == 0 {
+                0.0
+            } else {
+                d.total_space.saturating_sub(d.available_space) as f64 / d.total_space as f64 * 100.0
+            };
+            history.used_space.push(elapsed_ms, history_ms, used_percentage);
+
+            disks.push(history);
+        }
+
+        self.disks = disks;
+        if self.highlighted >= self.disks.len() {
+            self.highlighted = 0;
+        }
+    }
+
+    pub fn cycle_highlighted(&mut self) {
+        if !self.disks.is_empty() {
+            self.highlighted = (self.highlighted + 1) % self.disks.len();
+        }
+    }
+}
+
+pub struct InterfaceHistory {
+    // Counters from the latest sample, shown in the network panel
+    pub data: NetworkData,
+    // Bytes per second
+    pub rx_rate: TimeSeries,
+    pub tx_rate: TimeSeries,
+    last_sample_ms: f64
+}
+
+impl InterfaceHistory {
+    pub fn current_rx_rate(&self) -> f64 {
+        self.rx_rate.last().unwrap_or(0.0)
+    }
+
+    pub fn current_tx_rate(&self) -> f64 {
+        self.tx_rate.last().unwrap_or(0.0)
+    }
+}
+
+pub struct NetworkDataset {
+    // One entry per interface, sorted by name
+    pub interfaces: Vec<InterfaceHistory>
+}
+
+impl NetworkDataset {
+    pub fn new() -> Self {
+        Self {
+            interfaces: Vec::new()
+        }
+    }
+
+    // Insert rx/tx throughput for every interface
+    pub fn update_network_usage(&mut self, elapsed_ms: f64, history_ms: f64, network_data: Vec<NetworkData>) {
+        let mut interfaces: Vec<InterfaceHistory> = Vec::with_capacity(network_data.len());
+
+        for n in network_data {
+            let history = match self.interfaces.iter().position(|h| h.data.name == n.name) {
+                Some(i) => {
+                    let mut history = self.interfaces.swap_remove(i);
+                    let elapsed_s = (elapsed_ms - history.last_sample_ms) / 1000.0;
+                    if elapsed_s > 0.0 {
+                        let rx_rate = rate_per_second(history.data.total_received, n.total_received, elapsed_s);
+                        let tx_rate = rate_per_second(history.data.total_transmitted, n.total_transmitted, elapsed_s);
+                        history.rx_rate.push(elapsed_ms, history_ms, rx_rate);
+                        history.tx_rate.push(elapsed_ms, history_ms, tx_rate);
+                    }
+                    history.data = n;
+                    history.last_sample_ms = elapsed_ms;
+                    history
+                },
+                None => InterfaceHistory {
+                    data: n,
+                    rx_rate: TimeSeries::new(),
+                    tx_rate: TimeSeries::new(),
+                    last_sample_ms: elapsed_ms
+                }
+            };
+            interfaces.push(history);
+        }
+
+        self.interfaces = interfaces;
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ProcessColumn {
+    Pid,
+    Name,
+    User,
+    Cpu,
+    Memory,
+    DiskIo,
+    Status,
+    Command
+}
+
+impl ProcessColumn {
+    pub const ALL: [ProcessColumn; 8] = [
+        ProcessColumn::Pid,
+        ProcessColumn::Name,
+        ProcessColumn::User,
+        ProcessColumn::Cpu,
+        ProcessColumn::Memory,
+        ProcessColumn::DiskIo,
+        ProcessColumn::Status,
+        ProcessColumn::Command
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::Name => "Name",
+            ProcessColumn::User => "User",
+            ProcessColumn::Cpu => "CPU%",
+            ProcessColumn::Memory => "Memory",
+            ProcessColumn::DiskIo => "Disk R/W",
+            ProcessColumn::Status => "Status",
+            ProcessColumn::Command => "Command"
+        }
+    }
+
+    fn compare(&self, a: &ProcessData, b: &ProcessData) -> Ordering {
+        match self {
+            ProcessColumn::Pid => a.pid.cmp(&b.pid),
+            ProcessColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProcessColumn::User => a.user.cmp(&b.user),
+            ProcessColumn::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            ProcessColumn::Memory => a.memory.cmp(&b.memory),
+            ProcessColumn::DiskIo => (a.total_read_bytes + a.total_written_bytes)
+                .cmp(&(b.total_read_bytes + b.total_written_bytes)),
+            ProcessColumn::Status => a.status.cmp(&b.status),
+            ProcessColumn::Command => a.command.cmp(&b.command)
+        }
+    }
+}
+
+pub struct ProcessView {
+    // Every process from the latest refresh
+    processes: Vec<ProcessData>,
+    // Processes matching the filter, in sort order. This is what the table shows
+    pub rows: Vec<ProcessData>,
+    pub sort_column: ProcessColumn,
+    pub sort_descending: bool,
+    pub filter: String,
+    // True while the user is typing a filter after pressing '/'
+    pub filtering: bool,
+    // Selection follows the pid, so it stays on the same process when rows move around
+    pub selected_pid: Option<u32>,
+    pub table_state: TableState
+}
+
+impl ProcessView {
+    pub fn new() -> Self {
+        Self {
+            processes: Vec::new(),
+            rows: Vec::new(),
+            sort_column: ProcessColumn::Cpu,
+            sort_descending: true,
+            filter: String::new(),
+            filtering: false,
+            selected_pid: None,
+            table_state: TableState::default()
+        }
+    }
+
+    pub fn update_processes(&mut self, processes: Vec<ProcessData>) {
+        self.processes = processes;
+        self.apply();
+    }
+
+    // Rebuild the visible rows from the filter and sort settings
+    fn apply(&mut self) {
+        let filter = self.filter.to_lowercase();
+        let mut rows: Vec<ProcessData> = self.processes.iter()
+            .filter(|p| {
+                filter.is_empty()
+                    || p.pid.to_string().contains(&filter)
+                    || p.name.to_lowercase().contains(&filter)
+                    || p.user.to_lowercase().contains(&filter)
+                    || p.command.to_lowercase().contains(&filter)
+            })
+            .cloned()
+            .collect();
+
+        // Ties are broken by pid so rows don't jump around between refreshes
+        rows.sort_by(|a, b| {
+            let ordering = self.sort_column.compare(a, b).then(a.pid.cmp(&b.pid));
+            if self.sort_descending { ordering.reverse() } else { ordering }
+        });
+        self.rows = rows;
+
+        let selected = self.selected_index().or(if self.rows.is_empty() { None } else { Some(0) });
+        self.select_index(selected);
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected_pid.and_then(|pid| self.rows.iter().position(|p| p.pid == pid))
+    }
+
+    pub fn selected_process(&self) -> Option<&ProcessData> {
+        self.selected_index().map(|i| &self.rows[i])
+    }
+
+    fn select_index(&mut self, index: Option<usize>) {
+        self.selected_pid = index.map(|i| self.rows[i].pid);
+        self.table_state.select(index);
+    }
+
+    // Move the selection by delta rows, clamped to the table
+    pub fn move_selection(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.selected_index().unwrap_or(0) as isize;
+        let last = self.rows.len() as isize - 1;
+        self.select_index(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    pub fn select_first(&mut self) {
+        if !self.rows.is_empty() {
+            self.select_index(Some(0));
+        }
+    }
+
+    pub fn select_last(&mut self) {
+        if !self.rows.is_empty() {
+            self.select_index(Some(self.rows.len() - 1));
+        }
+    }
+
+    pub fn next_sort_column(&mut self) {
+        let i = ProcessColumn::ALL.iter().position(|c| *c == self.sort_column).unwrap_or(0);
+        self.sort_column = ProcessColumn::ALL[(i + 1) % ProcessColumn::ALL.len()];
+        self.apply();
+    }
+
+    pub fn previous_sort_column(&mut self) {
+        let i = ProcessColumn::ALL.iter().position(|c| *c == self.sort_column).unwrap_or(0);
+        self.sort_column = ProcessColumn::ALL[(i + ProcessColumn::ALL.len() - 1) % ProcessColumn::ALL.len()];
+        self.apply();
+    }
+
+    pub fn reverse_sort(&mut self) {
+        self.sort_descending = !self.sort_descending;
+        self.apply();
+    }
+
+    pub fn start_filtering(&mut self) {
+        self.filtering = true;
+    }
+
+    // Keep the filter but stop typing into it
+    pub fn stop_filtering(&mut self) {
+        self.filtering = false;
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter.clear();
+        self.filtering = false;
+        self.apply();
+    }
+
+    pub fn push_filter_char(&mut self, c: char) {
+        self.filter.push(c);
+        self.apply();
+    }
+
+    pub fn pop_filter_char(&mut self) {
+        self.filter.pop();
+        self.apply();
+    }
+}
+
+pub struct SignalDialog {
+    pub pid: u32,
+    pub name: String,
+    pub signals: Vec<Signal>,
+    pub selected: usize
+}
+
+impl SignalDialog {
+    // The usual signals come first, followed by everything else the platform supports
+    const COMMON_SIGNALS: [Signal; 4] = [Signal::Term, Signal::Kill, Signal::Stop, Signal::Continue];
+
+    pub fn new(pid: u32, name: String) -> Self {
+        let mut signals: Vec<Signal> = Self::COMMON_SIGNALS.iter()
+            .filter(|s| SUPPORTED_SIGNALS.contains(s))
+            .copied()
+            .collect();
+        signals.extend(SUPPORTED_SIGNALS.iter().filter(|s| !Self::COMMON_SIGNALS.contains(s)));
+
+        Self {
+            pid,
+            name,
+            signals,
+            selected: 0
         }
-        self.cpu_usage.push_back(
-            (
-                elapsed_ms,
-                value
-            )
-        );        
     }
 
-    pub fn get_cpu_usage_as_slice(&mut self) -> (&[(f64, f64)], &[(f64, f64)]) {
-        self.cpu_usage.make_contiguous();
-        self.cpu_usage.as_slices()
-    }   
+    pub fn selected_signal(&self) -> Option<Signal> {
+        self.signals.get(self.selected).copied()
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        if self.signals.is_empty() {
+            return;
+        }
+        let last = self.signals.len() as isize - 1;
+        self.selected = (self.selected as isize + delta).clamp(0, last) as usize;
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+    use super::*;
+    use crate::{
+        alerts::{Comparison, Metric},
+        metrics::{fixtures::{alert_rule, disk, network, process}, scripted::ScriptedSource}
+    };
+
+    fn frame(timestamp_ms: u64, cpu_usage: f64, received: u64) -> Snapshot {
+        Snapshot {
+            timestamp_ms,
+            avg_cpu_usage: cpu_usage,
+            memory: (6000, 2000, 8000),
+            networks: vec![network("eth0", received, 0)],
+            processes: vec![process(1, "init", 0.5, 0), process(42, "cargo", 80.0, 0), process(7, "bash", 2.0, 0)],
+            ..Snapshot::default()
+        }
+    }
+
+    fn samples(series: &TimeSeries) -> Vec<(f64, f64)> {
+        series.iter().copied().collect()
+    }
+
+    fn state_with(frames: Vec<Snapshot>, sample_interval: Duration) -> State {
+        let mut config = Config::default();
+        config.settings.sample_interval = sample_interval;
+        State::new(ScriptedSource::new(frames), &config)
+    }
+
+    #[test]
+    fn snapshots_feed_the_datasets() {
+        let mut state = state_with(
+            vec![frame(10_000, 10.0, 0), frame(10_500, 20.0, 1000), frame(11_000, 30.0, 3000)],
+            Duration::from_millis(1)
+        );
+        let elapsed_ms = state.wait_for_messages(3);
+
+        assert_eq!(elapsed_ms, 1000.0);
+        assert_eq!(samples(&state.cpu_dataset.cpu_usage), &[(0.0, 10.0), (500.0, 20.0), (1000.0, 30.0)]);
+        assert_eq!(state.memory_dataset.used_memory.last(), Some(2000.0));
+        // 2000 bytes over half a second
+        assert_eq!(state.network_dataset.interfaces[0].current_rx_rate(), 4000.0);
+    }
+
+    #[test]
+    fn seeking_a_replay_rebuilds_the_datasets() {
+        let frames = (0..10).map(|i| frame(100_000 + i * 5000, i as f64, 0)).collect();
+        let mut state = State::new_replay(Replay::new(frames).unwrap(), &Config::default());
+        state.toggle_playback();
+        state.refresh();
+        assert_eq!(samples(&state.cpu_dataset.cpu_usage), &[(0.0, 0.0)]);
+
+        // 25 s of history before 40 s into the recording
+        state.seek_replay(40_000);
+        assert_eq!(state.elapsed_ms, 40_000.0);
+        assert_eq!(
+            samples(&state.cpu_dataset.cpu_usage),
+            &[(15_000.0, 3.0), (20_000.0, 4.0), (25_000.0, 5.0), (30_000.0, 6.0), (35_000.0, 7.0), (40_000.0, 8.0)]
+        );
+
+        // Going back drops everything after the new position
+        state.replay.as_mut().unwrap().jump_input = Some("0:05".to_string());
+        state.confirm_replay_jump();
+        assert_eq!(state.elapsed_ms, 5000.0);
+        assert_eq!(samples(&state.cpu_dataset.cpu_usage), &[(0.0, 0.0), (5000.0, 1.0)]);
+    }
+
+    #[test]
+    fn datasets_are_exported_to_csv() {
+        let dir = std::env::temp_dir().join(format!("sys_tui_state_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = Config::default();
+        config.settings.sample_interval = Duration::from_millis(1);
+        config.export_dir = dir.clone();
+
+        let mut state = State::new(ScriptedSource::new(vec![frame(10_000, 10.0, 0), frame(10_500, 20.0, 1000)]), &config);
+        state.export_csv();
+        assert_eq!(state.status_message.as_deref(), Some("Nothing to export yet"));
+
+        state.wait_for_messages(2);
+        state.export_csv();
+        let path = state.status_message.as_deref().unwrap().strip_prefix("Exported to ").unwrap().to_string();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp_ms,elapsed_ms,cpu usage %,cpu frequency MHz,memory used MB,memory free MB,swap used MB,eth0 rx B/s,eth0 tx B/s");
+        // No frequency was reported and rates start with the second sample
+        assert_eq!(lines[1], "10000,0,10,,2000,6000,0,,");
+        assert_eq!(lines[2], "10500,500,20,,2000,6000,0,2000,0");
+    }
+
+    #[test]
+    fn processes_are_sorted_and_filtered() {
+        let mut state = state_with(vec![frame(0, 0.0, 0)], Duration::from_secs(60));
+        state.wait_for_messages(1);
+
+        let pids: Vec<u32> = state.process_view.rows.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![42, 7, 1]);
+
+        state.process_view.start_filtering();
+        for c in "BA".chars() {
+            state.process_view.push_filter_char(c);
+        }
+        let pids: Vec<u32> = state.process_view.rows.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![7]);
+    }
+
+    #[test]
+    fn signals_go_through_the_source() {
+        let frames = vec![frame(0, 0.0, 0)];
+        let source = ScriptedSource::new(frames);
+        let sent_signals = Arc::clone(&source.sent_signals);
+        let mut config = Config::default();
+        config.settings.sample_interval = Duration::from_secs(60);
+        let mut state = State::new(source, &config);
+        state.wait_for_messages(1);
+
+        state.set_graph_process();
+        state.open_signal_dialog();
+        state.confirm_signal_dialog();
+        state.wait_for_messages(1);
+
+        assert_eq!(*sent_signals.lock().unwrap(), vec![(42, Signal::Term)]);
+        assert_eq!(state.status_message.as_deref(), Some("Sent SIGTERM to 42 (cargo)"));
+    }
+
+    #[test]
+    fn signals_are_unavailable_during_replay() {
+        let mut state = State::new_replay(Replay::new(vec![frame(0, 0.0, 0)]).unwrap(), &Config::default());
+        state.refresh();
+        state.set_graph_process();
+        state.dispatch(Action::SendSignal);
+
+        assert!(state.signal_dialog.is_none());
+        assert_eq!(state.status_message.as_deref(), Some("Signals unavailable during replay"));
+    }
+
+    #[test]
+    fn seeking_does_not_log_alerts_twice() {
+        let config = Config {
+            alerts: vec![alert_rule(Metric::Cpu, Comparison::Above(50.0), 0)],
+            ..Config::default()
+        };
+        let frames = (0..10).map(|i| frame(100_000 + i * 5000, i as f64 * 10.0, 0)).collect();
+        let mut state = State::new_replay(Replay::new(frames).unwrap(), &config);
+        state.refresh();
+
+        // Up to 60% at 30 s
+        state.seek_replay(45_000);
+        assert_eq!(state.alerts.log.len(), 1);
+        assert!(state.alerts.is_firing(Panel::Cpu));
+
+        // Back before it fired and forward again over the same samples
+        state.seek_replay(-20_000);
+        assert!(!state.alerts.is_firing(Panel::Cpu));
+        state.seek_replay(20_000);
+        assert!(state.alerts.is_firing(Panel::Cpu));
+        assert_eq!(state.alerts.log.len(), 1);
+    }
+
+    #[test]
+    fn disk_usage_saturates_when_more_space_is_available_than_the_total() {
+        let mut dataset = DiskDataset::new();
+        dataset.update_disk_usage(0.0, 60_000.0, &[disk("sda1", "/", 1000, 1500, 0, 0)]);
+        assert_eq!(dataset.disks[0].used_space.last(), Some(0.0));
+    }
+
+    #[test]
+    fn time_series_are_drawn_in_order_after_wrapping() {
+        let mut series = TimeSeries::new();
+        // A 3 s window keeps the buffer small, so its start moves past the end and wraps around
+        for i in 0..99 {
+            series.push(i as f64 * 1000.0, 3000.0, i as f64);
+        }
+        series.make_contiguous();
+        assert_eq!(series.as_slice(), &[(95_000.0, 95.0), (96_000.0, 96.0), (97_000.0, 97.0), (98_000.0, 98.0)]);
+    }
+}
@@ -2,39 +2,95 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
-use std::ffi::OsString;
+use std::{collections::HashMap, ffi::{OsStr, OsString}, fs, io, path::Path, time::{SystemTime, UNIX_EPOCH}};
 
-use sysinfo::{Disks, Networks, System, CpuRefreshKind, RefreshKind, DiskKind};
+use crate::metrics::MetricsSource;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Disks, Networks, System, Users, CpuRefreshKind, RefreshKind, DiskKind, Pid, Signal, ThreadKind};
 // use tui::widgets::{ListItem, List, Dataset};
 
 pub struct SysInfo {
     pub disks: Disks,
     pub networks: Networks,
     pub system: System,
+    pub users: Users,
+    // Cumulative (read, written) bytes per block device name, e.g. "sda1"
+    pub disk_io: HashMap<String, (u64, u64)>,
 }
 
+#[derive(Clone)]
 pub struct DiskData {
     pub name: Box<OsString>,
     pub kind: DiskKind,
     pub file_system: Box<OsString>,
     pub total_space: u64,
     pub available_space: u64,
-    pub mount_point: Box<OsString>
+    pub mount_point: Box<OsString>,
+    pub read_bytes: u64,
+    pub written_bytes: u64
 }
 
 impl DiskData {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: Box<OsString>,
         kind: DiskKind,
         file_system: Box<OsString>, 
         total_space: u64,
         available_space: u64,
-        mount_point: Box<OsString>
+        mount_point: Box<OsString>,
+        read_bytes: u64,
+        written_bytes: u64
     ) -> Self {
-        Self {name, kind, file_system, total_space, available_space, mount_point}
+        Self {name, kind, file_system, total_space, available_space, mount_point, read_bytes, written_bytes}
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NetworkData {
+    pub name: String,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub total_packets_received: u64,
+    pub total_packets_transmitted: u64,
+    pub total_errors_on_received: u64,
+    pub total_errors_on_transmitted: u64
+}
+
+#[derive(Clone)]
+pub struct ProcessData {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub total_read_bytes: u64,
+    pub total_written_bytes: u64,
+    pub status: String,
+    pub command: String
+}
+
+// Everything the UI shows, taken at a single point in time.
+// Built on the sampler thread and sent over to State
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    // Milliseconds since the unix epoch when the sample was taken
+    pub timestamp_ms: u64,
+    // Missing in some containers and VMs
+    pub cpu_brand: Option<String>,
+    pub core_count: Option<usize>,
+    pub avg_cpu_usage: f64,
+    pub per_core_usage: Vec<f64>,
+    pub avg_cpu_frequency: Option<u64>,
+    pub per_core_frequency: Vec<u64>,
+    // (free, used, total) in MB
+    pub memory: (u64, u64, u64),
+    pub swap: (u64, u64, u64),
+    pub disks: Vec<DiskData>,
+    pub networks: Vec<NetworkData>,
+    pub processes: Vec<ProcessData>
+}
+
 impl SysInfo {
     pub fn new() -> Self {
         Self {
@@ -42,19 +98,15 @@ impl SysInfo {
             networks: Networks::new_with_refreshed_list(),
             system: System::new_with_specifics(
                 RefreshKind::new().with_cpu(CpuRefreshKind::everything()),
-            )
+            ),
+            users: Users::new_with_refreshed_list(),
+            disk_io: read_disk_io()
         }
     }
 
-    // Refresh all data on a single tick
-    pub fn refresh(&mut self) {
-        self.refresh_disks();
-        self.refresh_networks();
-        self.refresh_system();
-    }
-
     pub fn refresh_disks(&mut self) {
         self.disks.refresh();
+        self.disk_io = read_disk_io();
     }
 
     pub fn refresh_networks(&mut self) {
@@ -66,53 +118,224 @@ impl SysInfo {
         self.system.refresh_all();
     }
 
-    pub fn get_avg_cpu_usage(&self) -> f64 {
-        return self.system.global_cpu_info().cpu_usage() as f64;
+    pub fn get_processes_count(&self) -> usize{
+        self.system.processes().len()
+    }
+}
+
+impl MetricsSource for SysInfo {
+    // Refresh all data on a single tick
+    fn refresh(&mut self) {
+        self.refresh_disks();
+        self.refresh_networks();
+        self.refresh_system();
+    }
+
+    fn get_cpu_brand(&self) -> Option<String> {
+        self.system.cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .filter(|brand| !brand.is_empty())
     }
 
-    pub fn get_avg_cpu_frequency(&self) -> u64 {
+    fn get_core_count(&self) -> Option<usize> {
+        self.system.physical_core_count()
+    }
+
+    fn get_avg_cpu_usage(&self) -> f64 {
+        self.system.global_cpu_info().cpu_usage() as f64
+    }
+
+    fn get_per_core_usage(&self) -> Vec<f64> {
+        self.system.cpus().iter().map(|cpu| cpu.cpu_usage() as f64).collect()
+    }
+
+    // None when sysinfo sees no CPUs at all
+    fn get_avg_cpu_frequency(&self) -> Option<u64> {
         let mut cpu_freq_vec: Vec<u64> = Vec::new();
         for cpu in self.system.cpus().iter() {
             cpu_freq_vec.push(cpu.frequency())
         } 
-        return cpu_freq_vec.iter().sum::<u64>() / cpu_freq_vec.len() as u64;
+        if cpu_freq_vec.is_empty() {
+            return None;
+        }
+        Some(cpu_freq_vec.iter().sum::<u64>() / cpu_freq_vec.len() as u64)
     }
 
-    pub fn get_core_count(&self) -> usize {
-        self.system.physical_core_count().unwrap()
+    // Current frequency of every core in MHz
+    fn get_per_core_frequency(&self) -> Vec<u64> {
+        self.system.cpus().iter().map(|cpu| cpu.frequency()).collect()
     }
 
-    pub fn get_cpu_brand(&self) -> String {
-        self.system.cpus()[0].brand().to_string()
+    fn get_memory_data(&self) -> (u64, u64, u64) {
+        (
+            self.system.free_memory() / 1_000_000,
+            self.system.used_memory() / 1_000_000,
+            self.system.total_memory() / 1_000_000,
+        )
     }
 
-    pub fn get_processes_count(&self) -> usize{
-        self.system.processes().len()
+    fn get_swap_data(&self) -> (u64, u64, u64) {
+        (
+            self.system.free_swap() / 1_000_000,
+            self.system.used_swap() / 1_000_000,
+            self.system.total_swap() / 1_000_000,
+        )
     }
 
-    pub fn get_disk_data(&self) -> Vec<DiskData> {
+    fn get_disk_data(&self) -> Vec<DiskData> {
         self.disks.list().iter().map(|d| {
+            let (read_bytes, written_bytes) = self.disk_io
+                .get(&device_name(d.name()))
+                .copied()
+                .unwrap_or((0, 0));
             DiskData::new(
                 Box::new(d.name().to_owned()),
                 d.kind(),
                 Box::new(d.file_system().to_owned()),
                 d.total_space(),
                 d.available_space(),
-                Box::new(d.mount_point().as_os_str().to_owned())
+                Box::new(d.mount_point().as_os_str().to_owned()),
+                read_bytes,
+                written_bytes
             )
         }).collect::<Vec<DiskData>>()
     }
 
-    pub fn get_memory_data(&self) -> (u64, u64, u64) {
-        (
-            self.system.free_memory() / 1_000_000,
-            self.system.used_memory() / 1_000_000,
-            self.system.total_memory() / 1_000_000,
-        )
+    // Interfaces sorted by name so they keep their place in the UI between ticks
+    fn get_network_data(&self) -> Vec<NetworkData> {
+        let mut network_data = self.networks.list().iter().map(|(name, n)| {
+            NetworkData {
+                name: name.to_owned(),
+                total_received: n.total_received(),
+                total_transmitted: n.total_transmitted(),
+                total_packets_received: n.total_packets_received(),
+                total_packets_transmitted: n.total_packets_transmitted(),
+                total_errors_on_received: n.total_errors_on_received(),
+                total_errors_on_transmitted: n.total_errors_on_transmitted()
+            }
+        }).collect::<Vec<NetworkData>>();
+        network_data.sort_by(|a, b| a.name.cmp(&b.name));
+        network_data
+    }
+
+    fn get_process_data(&self) -> Vec<ProcessData> {
+        // On Linux sysinfo also lists every thread, only keep actual processes like top does
+        self.system.processes().values().filter(|p| p.thread_kind() != Some(ThreadKind::Userland)).map(|p| {
+            let user = p.user_id()
+                .and_then(|uid| self.users.get_user_by_id(uid))
+                .map(|u| u.name().to_string())
+                .unwrap_or_default();
+            // Kernel threads have no command line, fall back to their name
+            let command = if p.cmd().is_empty() {
+                format!("[{}]", p.name())
+            } else {
+                p.cmd().join(" ")
+            };
+            let disk_usage = p.disk_usage();
+
+            ProcessData {
+                pid: p.pid().as_u32(),
+                name: p.name().to_string(),
+                user,
+                cpu_usage: p.cpu_usage(),
+                memory: p.memory(),
+                total_read_bytes: disk_usage.total_read_bytes,
+                total_written_bytes: disk_usage.total_written_bytes,
+                status: p.status().to_string(),
+                command
+            }
+        }).collect::<Vec<ProcessData>>()
     }
+
+    fn send_signal(&self, pid: u32, signal: Signal) -> io::Result<()> {
+        let Some(process) = self.system.process(Pid::from_u32(pid)) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no such process"));
+        };
+        match process.kill_with(signal) {
+            Some(true) => Ok(()),
+            // sysinfo only reports failure, errno from kill(2) tells us why
+            Some(false) => Err(io::Error::last_os_error()),
+            None => Err(io::Error::new(io::ErrorKind::Unsupported, "signal not supported on this platform"))
+        }
+    }
+}
+
+// Conventional name of a signal, e.g. "SIGTERM"
+pub fn signal_name(signal: Signal) -> &'static str {
+    match signal {
+        Signal::Hangup => "SIGHUP",
+        Signal::Interrupt => "SIGINT",
+        Signal::Quit => "SIGQUIT",
+        Signal::Illegal => "SIGILL",
+        Signal::Trap => "SIGTRAP",
+        Signal::Abort => "SIGABRT",
+        Signal::IOT => "SIGIOT",
+        Signal::Bus => "SIGBUS",
+        Signal::FloatingPointException => "SIGFPE",
+        Signal::Kill => "SIGKILL",
+        Signal::User1 => "SIGUSR1",
+        Signal::Segv => "SIGSEGV",
+        Signal::User2 => "SIGUSR2",
+        Signal::Pipe => "SIGPIPE",
+        Signal::Alarm => "SIGALRM",
+        Signal::Term => "SIGTERM",
+        Signal::Child => "SIGCHLD",
+        Signal::Continue => "SIGCONT",
+        Signal::Stop => "SIGSTOP",
+        Signal::TSTP => "SIGTSTP",
+        Signal::TTIN => "SIGTTIN",
+        Signal::TTOU => "SIGTTOU",
+        Signal::Urgent => "SIGURG",
+        Signal::XCPU => "SIGXCPU",
+        Signal::XFSZ => "SIGXFSZ",
+        Signal::VirtualAlarm => "SIGVTALRM",
+        Signal::Profiling => "SIGPROF",
+        Signal::Winch => "SIGWINCH",
+        Signal::IO => "SIGIO",
+        Signal::Poll => "SIGPOLL",
+        Signal::Power => "SIGPWR",
+        Signal::Sys => "SIGSYS"
+    }
+}
+
+// sysinfo does not expose I/O counters per disk, so read them from /proc/diskstats.
+// Other platforms report no I/O for now.
+#[cfg(target_os = "linux")]
+fn read_disk_io() -> HashMap<String, (u64, u64)> {
+    let mut disk_io: HashMap<String, (u64, u64)> = HashMap::new();
+    let Ok(diskstats) = fs::read_to_string("/proc/diskstats") else {
+        return disk_io;
+    };
+
+    for line in diskstats.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            continue;
+        }
+        // Sectors in diskstats are always 512 bytes, whatever the device's real sector size
+        let sectors_read = fields[5].parse::<u64>().unwrap_or(0);
+        let sectors_written = fields[9].parse::<u64>().unwrap_or(0);
+        disk_io.insert(fields[2].to_string(), (sectors_read * 512, sectors_written * 512));
+    }
+    disk_io
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_disk_io() -> HashMap<String, (u64, u64)> {
+    HashMap::new()
+}
+
+// Map a disk name such as "/dev/mapper/root" to its kernel name ("dm-0")
+fn device_name(disk_name: &OsStr) -> String {
+    let path = Path::new(disk_name);
+    let resolved = fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    resolved
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
 }
 
 pub fn setup() -> SysInfo {
-    let state: SysInfo = SysInfo::new();
-    return state;
+    SysInfo::new()
 }
\ No newline at end of file
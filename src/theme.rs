@@ -0,0 +1,186 @@
+use ratatui::style::Color;
+
+pub const PRESET_NAMES: [&str; 5] = ["dark", "light", "solarized", "high-contrast", "monochrome"];
+
+// Every color the UI draws with. Widgets pick colors by role, never by name
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    pub background: Color,
+    // Plain text, table rows and chart axes
+    pub foreground: Color,
+    pub border: Color,
+    pub graph_border: Color,
+    // Gauges
+    pub accent: Color,
+    // Table headers, the aggregate CPU line and the selected row
+    pub highlight: Color,
+    // App description text
+    pub text_accent: Color,
+    // Usage levels, also used for the memory and disk lines
+    pub ok: Color,
+    pub warning: Color,
+    pub critical: Color,
+    // Lines that are drawn but not highlighted
+    pub muted: Color,
+    // One color per line on multi-line graphs, reused when there are more lines than colors
+    pub series: Vec<Color>
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "solarized" => Some(Self::solarized()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None
+        }
+    }
+
+    pub fn presets() -> Vec<Self> {
+        PRESET_NAMES.iter().filter_map(|name| Self::preset(name)).collect()
+    }
+
+    pub fn series_color(&self, i: usize) -> Color {
+        if self.series.is_empty() {
+            return self.foreground;
+        }
+        self.series[i % self.series.len()]
+    }
+
+    // The original look of the app
+    fn dark() -> Self {
+        Self {
+            name: "dark".to_string(),
+            background: Color::Black,
+            foreground: Color::White,
+            border: Color::White,
+            graph_border: Color::LightCyan,
+            accent: Color::Cyan,
+            highlight: Color::LightCyan,
+            text_accent: Color::Green,
+            ok: Color::LightGreen,
+            warning: Color::LightYellow,
+            critical: Color::LightRed,
+            muted: Color::DarkGray,
+            series: vec![
+                Color::LightCyan,
+                Color::LightGreen,
+                Color::LightYellow,
+                Color::LightMagenta,
+                Color::LightBlue,
+                Color::LightRed
+            ]
+        }
+    }
+
+    // Dark text on a white background, yellow is darkened to stay readable
+    fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            background: Color::White,
+            foreground: Color::Black,
+            border: Color::DarkGray,
+            graph_border: Color::Blue,
+            accent: Color::Blue,
+            highlight: Color::Blue,
+            text_accent: Color::Green,
+            ok: Color::Green,
+            warning: Color::Rgb(181, 137, 0),
+            critical: Color::Red,
+            muted: Color::Gray,
+            series: vec![
+                Color::Blue,
+                Color::Green,
+                Color::Magenta,
+                Color::Red,
+                Color::Cyan,
+                Color::Rgb(181, 137, 0)
+            ]
+        }
+    }
+
+    // https://ethanschoonover.com/solarized/ dark variant
+    fn solarized() -> Self {
+        const BASE03: Color = Color::Rgb(0, 43, 54);
+        const BASE01: Color = Color::Rgb(88, 110, 117);
+        const BASE0: Color = Color::Rgb(131, 148, 150);
+        const YELLOW: Color = Color::Rgb(181, 137, 0);
+        const ORANGE: Color = Color::Rgb(203, 75, 22);
+        const RED: Color = Color::Rgb(220, 50, 47);
+        const MAGENTA: Color = Color::Rgb(211, 54, 130);
+        const VIOLET: Color = Color::Rgb(108, 113, 196);
+        const BLUE: Color = Color::Rgb(38, 139, 210);
+        const CYAN: Color = Color::Rgb(42, 161, 152);
+        const GREEN: Color = Color::Rgb(133, 153, 0);
+
+        Self {
+            name: "solarized".to_string(),
+            background: BASE03,
+            foreground: BASE0,
+            border: BASE01,
+            graph_border: BLUE,
+            accent: CYAN,
+            highlight: BLUE,
+            text_accent: GREEN,
+            ok: GREEN,
+            warning: YELLOW,
+            critical: RED,
+            muted: BASE01,
+            series: vec![BLUE, GREEN, YELLOW, MAGENTA, VIOLET, ORANGE, CYAN]
+        }
+    }
+
+    // Only bright colors on black
+    fn high_contrast() -> Self {
+        Self {
+            name: "high-contrast".to_string(),
+            background: Color::Black,
+            foreground: Color::White,
+            border: Color::White,
+            graph_border: Color::LightYellow,
+            accent: Color::LightYellow,
+            highlight: Color::LightYellow,
+            text_accent: Color::White,
+            ok: Color::LightGreen,
+            warning: Color::LightYellow,
+            critical: Color::LightRed,
+            muted: Color::Gray,
+            series: vec![
+                Color::White,
+                Color::LightYellow,
+                Color::LightCyan,
+                Color::LightGreen,
+                Color::LightMagenta,
+                Color::LightRed
+            ]
+        }
+    }
+
+    // No hues at all, lines are told apart by the legend
+    fn monochrome() -> Self {
+        Self {
+            name: "monochrome".to_string(),
+            background: Color::Black,
+            foreground: Color::White,
+            border: Color::White,
+            graph_border: Color::White,
+            accent: Color::White,
+            highlight: Color::White,
+            text_accent: Color::White,
+            ok: Color::White,
+            warning: Color::White,
+            critical: Color::White,
+            muted: Color::DarkGray,
+            series: vec![Color::White, Color::Gray]
+        }
+    }
+}
@@ -7,48 +7,105 @@ use sysinfo::DiskKind;
 use ratatui::{
     Frame, 
     backend::Backend,
-    widgets::{Widget, Block, Borders, Paragraph, BorderType, List, ListItem, Gauge, Dataset, Chart, Axis, GraphType, Row, Table, Wrap, LegendPosition, Padding, canvas::Label},
+    widgets::{Widget, Block, Clear, ListState, block::{Title, Position}, Borders, Paragraph, BorderType, List, ListItem, Gauge, Dataset, Chart, Axis, GraphType, Row, Cell, Table, Wrap, LegendPosition, Padding, canvas::Label},
     layout::{Layout, Constraint, Direction, Rect, Alignment},
     style::{Color, Modifier, Style, Stylize},
     symbols::{block, Marker, border::Set},
-    text::Span
+    text::{Span, Line}
 };
-use std::{collections::HashMap, ffi::OsString, ops::Deref, rc::Rc, mem};
+use std::{collections::HashMap, ffi::OsString, ops::Deref, rc::Rc, mem, time::Duration};
 use crate::{
-    state::{State, Graph},
-    sys_poller::DiskData
+    replay,
+    state::{State, Graph, Panel, ProcessColumn},
+    sys_poller::{self, DiskData, NetworkData},
+    keymap::Action,
+    theme::Theme
 };
 
-const MIN_UPPER_SECTION_HEIGHT: u16 = 10;
-const MIN_CPU_HEIGHT: u16 = 12;
-const MIN_MEM_HEIGHT: u16 = 12;
-const MIN_DISK_HEIGHT: u16 = 8;
+// The description and the first few keys, the rest are in the key list overlay
+const MIN_UPPER_SECTION_HEIGHT: u16 = 6;
 
-const MIN_TOTAL_HEIGHT: u16 = MIN_UPPER_SECTION_HEIGHT + MIN_CPU_HEIGHT + MIN_MEM_HEIGHT + MIN_DISK_HEIGHT;
+// Below this nothing useful fits, a placeholder is drawn instead
+pub const MIN_WIDTH: u16 = 60;
+pub const MIN_HEIGHT: u16 = 20;
+// Below this height the description and usage panels are dropped
+const COLLAPSE_UPPER_SECTION_HEIGHT: u16 = 30;
+// Below this width the graph goes under the info blocks instead of next to them
+const STACKED_LAYOUT_WIDTH: u16 = 100;
+// Rows the two rows of info blocks keep in that layout, enough for the text and gauge
+// of the CPU block, whatever the graph size. The graph keeps a few rows on small screens
+const MIN_STACKED_INFO_HEIGHT: u16 = 2 * (CPU_INFO_HEIGHT + USAGE_GAUGE_HEIGHT + 2);
+const MIN_STACKED_GRAPH_HEIGHT: u16 = 8;
+
+// Borders of panels with a firing alert switch between two styles every this many frames
+const ALERT_BLINK_FRAMES: u64 = 10;
+
+// Rows of the text above the CPU and memory gauges, and of the gauges themselves
+const CPU_INFO_HEIGHT: u16 = 4;
+const MEM_INFO_HEIGHT: u16 = 3;
+const USAGE_GAUGE_HEIGHT: u16 = 3;
+// Header and rows of the disk table
+const DISK_TABLE_HEIGHT: u16 = 7;
+// Interfaces the network block makes room for before the others get any spare rows
+const MIN_LISTED_INTERFACES: u16 = 4;
+
+// Widest key list entry with some room to spare
+const KEY_LIST_COLUMN_WIDTH: u16 = 42;
+
+// Width of the label column and of each disk column in the disk table
+const DISK_LABEL_WIDTH: u16 = 21;
+const DISK_COLUMN_WIDTH: u16 = 15;
 
 
 pub fn create_ui(f: &mut Frame, state: &mut State, elapsed_ms: f64) {
-    let main_chunk: Rc<[Rect]> = Layout::default()
+    state.make_series_contiguous();
+
+    if f.size().width < MIN_WIDTH || f.size().height < MIN_HEIGHT {
+        draw_too_small(f, state);
+        // Nothing on screen can be clicked
+        state.areas.clear();
+        return;
+    }
+
+    // The status line is split off first, the percentages below would squeeze it out otherwise
+    let screen: Rc<[Rect]> = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([
-            Constraint::Min(MIN_UPPER_SECTION_HEIGHT),
-            Constraint::Percentage(85),
-        ].as_ref()
-    ).split(f.size());
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(f.size());
+
+    let sections: Rc<[Rect]> = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            if f.size().height < COLLAPSE_UPPER_SECTION_HEIGHT {
+                [
+                    Constraint::Length(0),
+                    Constraint::Min(0),
+                ]
+            } else {
+                [
+                    Constraint::Min(MIN_UPPER_SECTION_HEIGHT),
+                    Constraint::Percentage(85),
+                ]
+            }
+    ).split(screen[0]);
+    let main_chunk: [Rect; 3] = [sections[0], sections[1], screen[1]];
 
     // Get all areas and their respective names as a HashMap
-    let areas: HashMap<String, Rect> = separate_areas(f, main_chunk.deref(), &state);
+    let areas: HashMap<String, Rect> = separate_areas(f, &main_chunk, state);
 
     // Draw all blocks and borders etc.
-    let blocks: HashMap<String, Block<'static>> = draw_blocks(f, &areas);
+    state.frame_count += 1;
+    let blink_on = (state.frame_count / ALERT_BLINK_FRAMES).is_multiple_of(2);
+    let blocks: HashMap<String, Block<'static>> = draw_blocks(f, &areas, state, blink_on);
 
     // Draw actual data
-    draw_description(f, &blocks.get("desc_block").unwrap().inner(*areas.get("desc_area").unwrap()));
-    draw_usage(f, &blocks.get("app_usage_block").unwrap().inner(*areas.get("app_usage_area").unwrap()));
+    draw_description(f, &state.theme, &blocks.get("desc_block").unwrap().inner(*areas.get("desc_area").unwrap()));
+    draw_usage(f, state, &blocks.get("app_usage_block").unwrap().inner(*areas.get("app_usage_area").unwrap()));
     draw_cpu(f, state, &blocks.get("cpu_block").unwrap().inner(*areas.get("cpu_info").unwrap()));
     draw_memory(f, state, &blocks.get("mem_block").unwrap().inner(*areas.get("mem_info").unwrap()));
     draw_disks(f, state, &blocks.get("disks_block").unwrap().inner(*areas.get("disk_info").unwrap()));
+    draw_network(f, state, &blocks.get("net_block").unwrap().inner(*areas.get("net_info").unwrap()));
     match state.graph {
         Graph::CPU => {
             draw_cpu_graph(
@@ -58,11 +115,56 @@ pub fn create_ui(f: &mut Frame, state: &mut State, elapsed_ms: f64) {
                 elapsed_ms
             )
         },
-        Graph::MEMORY=> {},
-        Graph::DISK => {},
+        Graph::MEMORY => {
+            draw_memory_graph(
+                f,
+                state,
+                &blocks.get("graph_block").unwrap().inner(*areas.get("graph_area").unwrap()),
+                elapsed_ms
+            )
+        },
+        Graph::DISK => {
+            draw_disk_graph(
+                f,
+                state,
+                &blocks.get("graph_block").unwrap().inner(*areas.get("graph_area").unwrap()),
+                elapsed_ms
+            )
+        },
+        Graph::NETWORK => {
+            draw_network_graph(
+                f,
+                state,
+                &blocks.get("graph_block").unwrap().inner(*areas.get("graph_area").unwrap()),
+                elapsed_ms
+            )
+        },
+        Graph::PROCESS => {
+            draw_process_table(
+                f,
+                state,
+                &blocks.get("graph_block").unwrap().inner(*areas.get("graph_area").unwrap())
+            )
+        },
+        Graph::ALERTS => {
+            draw_alert_log(
+                f,
+                state,
+                &blocks.get("graph_block").unwrap().inner(*areas.get("graph_area").unwrap())
+            )
+        },
     }
-    
-    
+
+    draw_status(f, state, areas.get("status_area").unwrap());
+    if state.signal_dialog.is_some() {
+        draw_signal_dialog(f, state, areas.get("graph_area").unwrap());
+    }
+    if state.help_offset.is_some() {
+        draw_key_list(f, state, &screen[0]);
+    }
+
+    // Kept for mouse handling until the next frame
+    state.areas = areas;
 }
 
 // Define all areas that will containg widgets
@@ -77,6 +179,8 @@ fn separate_areas(f: &Frame, area_arr: &[Rect], state: &State) -> HashMap<String
     // |----------|  (which graph   | <-- graph section
     // | more     |   is selected   |
     // | info     |   by the user)  |
+    // |----------|                 |
+    // | network  |                 |
     // ------------------------------
     let mut areas: HashMap<String, Rect> = HashMap::new();
 
@@ -91,55 +195,132 @@ fn separate_areas(f: &Frame, area_arr: &[Rect], state: &State) -> HashMap<String
         .split(area_arr[0]);
     areas.insert("desc_area".to_owned(), uppermost_section[0]);
     areas.insert("app_usage_area".to_owned(), uppermost_section[1]);
+    areas.insert("status_area".to_owned(), area_arr[2]);
     
-    let lower_section: Rc<[Rect]> = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(
-            [ 
-                Constraint::Percentage(100 - state.graph_size_percentage),
-                Constraint::Percentage(state.graph_size_percentage)
-            ].as_ref()
-        )
-        .split(area_arr[1]);
+    // On narrow screens the graph goes below the info blocks, which are laid out as a 2x2 grid:
+    // ------------------------------
+    // |  cpu info   |  memory info |
+    // |-------------|--------------|
+    // |  disk info  | network info |
+    // |----------------------------|
+    // |           graph            |
+    // ------------------------------
+    let stacked = f.size().width < STACKED_LAYOUT_WIDTH;
+
+    let lower_section: Rc<[Rect]> = if stacked {
+        let height = area_arr[1].height;
+        let info_height = (height as u32 * (100 - state.graph_size_percentage) as u32 / 100) as u16;
+        let min_info_height = MIN_STACKED_INFO_HEIGHT.min(height.saturating_sub(MIN_STACKED_GRAPH_HEIGHT));
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(info_height.max(min_info_height)), Constraint::Min(0)])
+            .split(area_arr[1])
+    } else {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [ 
+                    Constraint::Percentage(100 - state.graph_size_percentage),
+                    Constraint::Percentage(state.graph_size_percentage)
+                ].as_ref()
+            )
+            .split(area_arr[1])
+    };
+    areas.insert("info_area".to_owned(), lower_section[0]);
     areas.insert("graph_area".to_owned(), lower_section[1]);
 
+    if stacked {
+        let info_rows: Rc<[Rect]> = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(lower_section[0]);
+        let upper_row: Rc<[Rect]> = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(info_rows[0]);
+        let lower_row: Rc<[Rect]> = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(info_rows[1]);
+        areas.insert("cpu_info".to_owned(), upper_row[0]);
+        areas.insert("mem_info".to_owned(), upper_row[1]);
+        areas.insert("disk_info".to_owned(), lower_row[0]);
+        areas.insert("net_info".to_owned(), lower_row[1]);
+        return areas;
+    }
+
+    // Each block gets the rows its contents need and the rows left over are shared evenly.
+    // Interfaces past the first few are only listed when there is room for them
+    let needs = [
+        CPU_INFO_HEIGHT + USAGE_GAUGE_HEIGHT + 2,
+        MEM_INFO_HEIGHT + USAGE_GAUGE_HEIGHT + 2,
+        DISK_TABLE_HEIGHT + 2,
+        1 + (state.network_dataset.interfaces.len() as u16).min(MIN_LISTED_INTERFACES) + 2
+    ];
     let info_section: Rc<[Rect]> = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(
-            if f.size().height <= MIN_TOTAL_HEIGHT {
-                [
-                    Constraint::Min(MIN_CPU_HEIGHT),
-                    Constraint::Min(MIN_MEM_HEIGHT),
-                    Constraint::Min(MIN_DISK_HEIGHT)
-                ].as_ref()
-            } else {
-                [
-                    Constraint::Percentage(33),
-                    Constraint::Percentage(33),
-                    Constraint::Percentage(33)
-                ].as_ref()
-            }
-            
-        )
+        .constraints(share_rows(lower_section[0].height, &needs).into_iter().map(Constraint::Length))
         .split(lower_section[0]);
     areas.insert("cpu_info".to_owned(), info_section[0]);
     areas.insert("mem_info".to_owned(), info_section[1]);
     areas.insert("disk_info".to_owned(), info_section[2]);
+    areas.insert("net_info".to_owned(), info_section[3]);
 
     areas
 }
 
-fn draw_blocks<'a>(f: &mut Frame, areas: &HashMap<String, Rect>) -> HashMap<String, Block<'a>> {
+// Splits the rows between blocks needing the given number of rows.
+// When they don't all fit each block shrinks in proportion to its needs
+fn share_rows(height: u16, needs: &[u16]) -> Vec<u16> {
+    let total: u16 = needs.iter().sum();
+    let count = needs.len() as u16;
+    if total <= height {
+        let extra = height - total;
+        return needs.iter().enumerate()
+            .map(|(i, need)| need + extra / count + u16::from((i as u16) < extra % count))
+            .collect();
+    }
+
+    let mut heights: Vec<u16> = needs.iter()
+        .map(|need| (*need as u32 * height as u32 / total as u32) as u16)
+        .collect();
+    let left = height - heights.iter().sum::<u16>();
+    for height in heights.iter_mut().take(left as usize) {
+        *height += 1;
+    }
+    heights
+}
+
+fn draw_blocks<'a>(f: &mut Frame, areas: &HashMap<String, Rect>, state: &State, blink_on: bool) -> HashMap<String, Block<'a>> {
+    let theme = &state.theme;
+    // Panels with a firing alert blink, otherwise the panel clicked last stands out
+    let border_style = |panel: Panel, color: Color| {
+        if state.alerts.is_firing(panel) && blink_on {
+            Style::default().fg(theme.background).bg(theme.critical).add_modifier(Modifier::BOLD)
+        } else if state.alerts.is_firing(panel) {
+            Style::default().fg(theme.critical).bg(theme.background).add_modifier(Modifier::BOLD)
+        } else if state.focused == Some(panel) {
+            Style::default().fg(theme.highlight).bg(theme.background).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color).bg(theme.background)
+        }
+    };
+
     let mut blocks: HashMap<String, Block> = HashMap::new();
     let description_block = Block::default()
         .title("App Description")
         .borders(Borders::ALL)
         .border_style(
             Style::default()
-                .fg(Color::White)
-                .bg(Color::Black)
+                .fg(theme.border)
+                .bg(theme.background)
         )
-        .border_type(BorderType::Plain);
+        .border_type(BorderType::Plain)
+        .style(
+            Style::default()
+                .fg(theme.foreground)
+                .bg(theme.background)
+        );
     blocks.insert("desc_block".to_string(), description_block.clone());
     f.render_widget(description_block, *areas.get("desc_area").unwrap());
     
@@ -149,10 +330,15 @@ fn draw_blocks<'a>(f: &mut Frame, areas: &HashMap<String, Rect>) -> HashMap<Stri
         .borders(Borders::ALL)
         .border_style(
             Style::default()
-                .fg(Color::White)
-                .bg(Color::Black)
+                .fg(theme.border)
+                .bg(theme.background)
         )
-        .border_type(BorderType::Rounded);
+        .border_type(BorderType::Rounded)
+        .style(
+            Style::default()
+                .fg(theme.foreground)
+                .bg(theme.background)
+        );
     blocks.insert("app_usage_block".to_string(), app_usage_block.clone());
     f.render_widget(app_usage_block, *areas.get("app_usage_area").unwrap());
     
@@ -160,12 +346,13 @@ fn draw_blocks<'a>(f: &mut Frame, areas: &HashMap<String, Rect>) -> HashMap<Stri
     let cpu_block = Block::default()
         .title("CPU Information")
         .borders(Borders::ALL)
-        .border_style(
+        .border_style(border_style(Panel::Cpu, theme.border))
+        .border_type(BorderType::Rounded)
+        .style(
             Style::default()
-                .fg(Color::White)
-                .bg(Color::Black)
-        )
-        .border_type(BorderType::Rounded);
+                .fg(theme.foreground)
+                .bg(theme.background)
+        );
     blocks.insert("cpu_block".to_string(), cpu_block.clone());
     f.render_widget(cpu_block, *areas.get("cpu_info").unwrap());
     
@@ -173,12 +360,13 @@ fn draw_blocks<'a>(f: &mut Frame, areas: &HashMap<String, Rect>) -> HashMap<Stri
     let mem_block = Block::default()
         .title("Memory Information")
         .borders(Borders::ALL)
-        .border_style(
+        .border_style(border_style(Panel::Memory, theme.border))
+        .border_type(BorderType::Rounded)
+        .style(
             Style::default()
-                .fg(Color::White)
-                .bg(Color::Black)
-        )
-        .border_type(BorderType::Rounded);
+                .fg(theme.foreground)
+                .bg(theme.background)
+        );
     blocks.insert("mem_block".to_string(), mem_block.clone());
     f.render_widget(mem_block, *areas.get("mem_info").unwrap());
     
@@ -186,36 +374,116 @@ fn draw_blocks<'a>(f: &mut Frame, areas: &HashMap<String, Rect>) -> HashMap<Stri
     let disks_block = Block::default()
         .title("Disks Information")
         .borders(Borders::ALL)
-        .border_style(
+        .border_style(border_style(Panel::Disks, theme.border))
+        .border_type(BorderType::Rounded)
+        .style(
             Style::default()
-                .fg(Color::White)
-                .bg(Color::Black)
-        )
-        .border_type(BorderType::Rounded);
+                .fg(theme.foreground)
+                .bg(theme.background)
+        );
     blocks.insert("disks_block".to_string(), disks_block.clone());
     f.render_widget(disks_block, *areas.get("disk_info").unwrap());
     
 
+    let net_block = Block::default()
+        .title("Network Information")
+        .borders(Borders::ALL)
+        .border_style(border_style(Panel::Network, theme.border))
+        .border_type(BorderType::Rounded)
+        .style(
+            Style::default()
+                .fg(theme.foreground)
+                .bg(theme.background)
+        );
+    blocks.insert("net_block".to_string(), net_block.clone());
+    f.render_widget(net_block, *areas.get("net_info").unwrap());
+    
+
     let graph_block = Block::default()
-        .title("Graph")
+        .title(graph_tabs(state.graph, theme))
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
-        .border_style(
+        .border_style(border_style(Panel::Graph, theme.graph_border))
+        .border_type(BorderType::Double)
+        .style(
             Style::default()
-                .fg(Color::LightCyan)
-                .bg(Color::Black)
-        )
-        .border_type(BorderType::Double);
+                .fg(theme.foreground)
+                .bg(theme.background)
+        );
     blocks.insert("graph_block".to_string(), graph_block.clone());
     f.render_widget(graph_block, *areas.get("graph_area").unwrap());
 
     blocks
 }
 
-fn draw_description(f: &mut Frame, area: &Rect) {
+// Tabs shown as the title of the graph pane, they can be clicked to switch graphs
+const GRAPH_TABS: [(Graph, &str); 6] = [
+    (Graph::CPU, "CPU"),
+    (Graph::MEMORY, "Memory"),
+    (Graph::DISK, "Disk"),
+    (Graph::NETWORK, "Network"),
+    (Graph::PROCESS, "Processes"),
+    (Graph::ALERTS, "Alerts")
+];
+const GRAPH_TAB_SEPARATOR: &str = "│";
+
+fn graph_tabs<'a>(current: Graph, theme: &Theme) -> Line<'a> {
+    let mut spans: Vec<Span> = Vec::new();
+    for (i, (graph, name)) in GRAPH_TABS.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::from(GRAPH_TAB_SEPARATOR));
+        }
+        let style = if *graph == current {
+            Style::default().fg(theme.background).bg(theme.highlight)
+        } else {
+            Style::default().fg(theme.foreground)
+        };
+        spans.push(Span::styled(format!(" {name} "), style));
+    }
+    Line::from(spans)
+}
+
+// Which tab of the graph title is at the given column. The title is centered like ratatui centers it
+pub fn graph_tab_at(graph_area: &Rect, column: u16) -> Option<Graph> {
+    let tab_widths: Vec<u16> = GRAPH_TABS.iter().map(|(_, name)| name.len() as u16 + 2).collect();
+    let title_width = tab_widths.iter().sum::<u16>() + (GRAPH_TABS.len() as u16 - 1);
+    let mut x = graph_area.x + graph_area.width.saturating_sub(title_width) / 2;
+    for ((graph, _), width) in GRAPH_TABS.iter().zip(tab_widths) {
+        if (x..x + width).contains(&column) {
+            return Some(*graph);
+        }
+        x += width + 1;
+    }
+    None
+}
+
+fn draw_too_small(f: &mut Frame, state: &State) {
+    let size = f.size();
+    let theme = &state.theme;
+    let message = format!(
+        "Terminal too small\n{}x{}, need at least {MIN_WIDTH}x{MIN_HEIGHT}",
+        size.width, size.height
+    );
+    let lines = message.lines().count() as u16;
+    let area = Rect::new(size.x, size.y + size.height.saturating_sub(lines) / 2, size.width, lines.min(size.height));
+
+    f.render_widget(Block::default().style(Style::default().bg(theme.background)), size);
+    f.render_widget(
+        Paragraph::new(message)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .style(
+                Style::default()
+                    .fg(theme.warning)
+                    .bg(theme.background)
+            ),
+        area
+    );
+}
+
+fn draw_description(f: &mut Frame, theme: &Theme, area: &Rect) {
     // App description
-    const APP_DESCRIPTION: &str = r#"
-    This app allows the user to monitor CPU usage, memory and disks.
+    const APP_DESCRIPTION: &str = r#"This app allows the user to monitor CPU usage, memory and disks.
     Monitoring can be done by switching the current graph seen on the right.
     "#;
     let app_desc = Paragraph::new(APP_DESCRIPTION)
@@ -223,25 +491,59 @@ fn draw_description(f: &mut Frame, area: &Rect) {
         .alignment(Alignment::Left)
         .style(
             Style::default()
-            .fg(Color::Green)
-            .bg(Color::Black)
+            .fg(theme.text_accent)
+            .bg(theme.background)
         );
     f.render_widget(app_desc, *area);
 }
 
-fn draw_usage(f: &mut Frame, area: &Rect) {
-    // App usage definition
-    const APP_USAGE: &str = r#"
-    App usage:
-    Q:          Quit
-    C:          Show CPU Graph
-    M:          Show Memory Graph
-    D:          Show Disk Graph
-    A:          Expand Graph Size
-    S:          Reduce Graph Size
-    "#;
-    let app_desc = Paragraph::new(APP_USAGE);
-    f.render_widget(app_desc, *area);
+// Generated from the active key map so that rebound keys show up in the help
+fn help_line(state: &State, action: Action) -> Option<Line<'static>> {
+    let keys: Vec<String> = state.keymap.chords(action).iter().map(|chord| chord.to_string()).collect();
+    if keys.is_empty() {
+        return None;
+    }
+    Some(Line::from(vec![
+        Span::styled(format!("{:<11} ", keys.join(", ")), Style::default().add_modifier(Modifier::BOLD)),
+        Span::from(action.description())
+    ]))
+}
+
+// Every binding, process actions get their own section as they do nothing elsewhere
+fn key_list(state: &State) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line> = Action::ALL.iter()
+        .filter(|action| !action.is_process_action() && !action.is_replay_action())
+        .filter_map(|action| help_line(state, *action))
+        .collect();
+    if state.replay.is_some() {
+        lines.push(Line::from("Replay:"));
+        lines.extend(
+            Action::ALL.iter()
+                .filter(|action| action.is_replay_action())
+                .filter_map(|action| help_line(state, *action))
+        );
+    }
+    lines.push(Line::from("Processes and alert log:"));
+    lines.extend(
+        Action::ALL.iter()
+            .filter(|action| action.is_process_action())
+            .filter_map(|action| help_line(state, *action))
+    );
+    lines
+}
+
+fn draw_usage(f: &mut Frame, state: &State, area: &Rect) {
+    // Only the first few bindings fit, the key to the full list comes first
+    let mut lines: Vec<Line> = help_line(state, Action::ToggleHelp).into_iter().collect();
+    lines.extend(
+        Action::ALL.iter()
+            .filter(|action| **action != Action::ToggleHelp)
+            .filter(|action| !action.is_process_action() && !action.is_replay_action())
+            .filter_map(|action| help_line(state, *action))
+    );
+
+    let app_usage = Paragraph::new(lines).block(Block::default().padding(Padding::horizontal(1)));
+    f.render_widget(app_usage, *area);
 }
 
 fn draw_cpu(f: &mut Frame, state: &State, area: &Rect) {
@@ -249,49 +551,65 @@ fn draw_cpu(f: &mut Frame, state: &State, area: &Rect) {
     // We will display the CPU brand, vendor_id
     // frequency and usage across all cores 
     //  -------------------------
-    // |        CPU brand       | 
+    // |  CPU Paragraph Info    |
     // |------------------------|
-    // |           |            |
-    // |           |            |
-    // |    CPU    |            |
-    // | Paragraph |   VGauge   |
-    // |    Info   |  (Usage %) |
-    // |           |            |
-    // |           |            |
+    // |  Per core usage grid   |
+    // |------------------------|
+    // |   Gauge (Usage %)      |
     //  -------------------------
 
-    // Separate upper and lower section
+    let theme = &state.theme;
+
+    // Separate upper, middle and lower section.
+    // The gauge is the main reading and is given its rows first, then the text.
+    // The per-core grid gets what is left, and the margin goes when rows are short
+    let margin = if area.height >= CPU_INFO_HEIGHT + 1 + USAGE_GAUGE_HEIGHT + 2 { 1 } else { 0 };
+    let gauge_height = USAGE_GAUGE_HEIGHT.min(area.height);
+    let info_height = CPU_INFO_HEIGHT.min(area.height.saturating_sub(gauge_height + 2 * margin));
     let cpu_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Min(5),
-                Constraint::Min(1)
+                Constraint::Length(info_height),
+                Constraint::Min(0),
+                Constraint::Length(gauge_height)
             ]
         )
-        .vertical_margin(1)
+        .vertical_margin(margin)
         .split(*area);
     let upper_section = cpu_layout[0];
-    let lower_section = cpu_layout[1];
+    let middle_section = cpu_layout[1];
+    let lower_section = cpu_layout[2];
 
     // Draw some CPU information
-    let cpu_brand = state.system.get_cpu_brand();
-    let avg_frequency: f64 = state.system.get_avg_cpu_frequency() as f64 / 1000.0;
-    let core_count = state.system.get_core_count();
-    let processes_count = state.system.get_processes_count();
+    // Containers and VMs don't always report these
+    let cpu_brand = state.snapshot.cpu_brand.as_deref().unwrap_or("Unknown CPU");
+    let avg_frequency = format_frequency(state.snapshot.avg_cpu_frequency);
+    let min_frequency = format_frequency(state.cpu_dataset.min_frequency);
+    let max_frequency = format_frequency(state.cpu_dataset.max_frequency);
+    let core_count = state.snapshot.core_count.map_or("n/a".to_string(), |count| count.to_string());
+    let processes_count = state.snapshot.processes.len();
 
     let cpu_info = format!(
     r#"{cpu_brand}
-Base speed: {avg_frequency:.2} GHz
-Cores: {core_count}
-Processes: {processes_count}
+Avg frequency: {avg_frequency}
+Session min/max: {min_frequency} / {max_frequency}
+Cores: {core_count}  Processes: {processes_count}
 "#);
 
     let info_paragraph = Paragraph::new(cpu_info);    
     f.render_widget(info_paragraph, upper_section);
 
+    draw_core_grid(
+        f,
+        theme,
+        &state.snapshot.per_core_usage,
+        &state.cpu_dataset.per_core_frequency,
+        &middle_section
+    );
+
     // Draw gauge for CPU usage
-    let usage = state.system.get_avg_cpu_usage();
+    let usage = state.snapshot.avg_cpu_usage;
     let label = format!("{usage:.2} %");
     
     let freq_gauge = Gauge::default()
@@ -304,38 +622,93 @@ Processes: {processes_count}
         )
         .gauge_style(
             Style::default()
-                .fg(Color::Cyan)
-                .bg(Color::Black)
+                .fg(theme.accent)
+                .bg(theme.background)
         )
-        .percent(usage as u16)
+        .percent((usage as u16).min(100))
         .label(label);
         f.render_widget(freq_gauge, lower_section);
 }
 
+fn draw_core_grid(f: &mut Frame, theme: &Theme, per_core_usage: &[f64], per_core_frequency: &[u64], area: &Rect) {
+    // Each core gets a cell like " 3 ▆▆▆  82% 2.1G" when there is room for all of them,
+    // otherwise a single bar character per core so that big machines still fit
+    const CELL_WIDTH: u16 = 18;
+    const BAR_WIDTH: usize = 4;
+    const BAR_LEVELS: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+    let usage_color = |usage: f64| {
+        if usage >= 80.0 {
+            theme.critical
+        } else if usage >= 50.0 {
+            theme.warning
+        } else {
+            theme.ok
+        }
+    };
+
+    let columns = (area.width / CELL_WIDTH).max(1) as usize;
+    let rows_needed = per_core_usage.len().div_ceil(columns);
+
+    let lines: Vec<Line> = if rows_needed <= area.height as usize {
+        per_core_usage.chunks(columns).enumerate().map(|(row, chunk_usages)| {
+            let row_start = row * columns;
+            Line::from(chunk_usages.iter().enumerate().map(|(i, usage)| {
+                let filled = (((usage / 100.0) * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+                let bar = format!("{}{}", "▆".repeat(filled), " ".repeat(BAR_WIDTH - filled));
+                let core = row_start + i;
+                let frequency = per_core_frequency.get(core).map_or(0.0, |f| *f as f64 / 1000.0);
+                Span::styled(
+                    format!("{core:>2} {bar} {usage:>3.0}% {frequency:.1}G "),
+                    Style::default().fg(usage_color(*usage))
+                )
+            }).collect::<Vec<Span>>())
+        }).collect()
+    } else {
+        let per_line = area.width.max(1) as usize;
+        per_core_usage.chunks(per_line).map(|chunk_usages| {
+            Line::from(chunk_usages.iter().map(|usage| {
+                let level = ((usage / 100.0) * 8.0).round().clamp(0.0, 8.0) as usize;
+                Span::styled(BAR_LEVELS[level], Style::default().fg(usage_color(*usage)))
+            }).collect::<Vec<Span>>())
+        }).collect()
+    };
+
+    f.render_widget(Paragraph::new(lines), *area);
+}
+
 fn draw_memory(f: &mut Frame, state: &State, area: &Rect) {
-    let (free_memory, used_memory, total_memory) = state.system.get_memory_data();
+    let theme = &state.theme;
+    let (free_memory, used_memory, total_memory) = state.snapshot.memory;
 
+    // Same as the CPU panel, the gauge gets its rows first
+    let margin = if area.height >= MEM_INFO_HEIGHT + USAGE_GAUGE_HEIGHT + 2 { 1 } else { 0 };
+    let gauge_height = USAGE_GAUGE_HEIGHT.min(area.height);
     let sections = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
-                Constraint::Percentage(50),
-                Constraint::Percentage(50)
+                Constraint::Min(0),
+                Constraint::Length(gauge_height)
             ]
         )
-        .vertical_margin(1)
+        .vertical_margin(margin)
         .split(*area);
 
-    let mem_text = format!(r#"
-Total RAM: {total_memory} MB
+    let mem_text = format!(r#"Total RAM: {total_memory} MB
 Used RAM: {used_memory} MB
-Free RAM: {free_memory} MB
-    "#);
+Free RAM: {free_memory} MB"#);
 
     let mem_paragraph = Paragraph::new(mem_text);
     f.render_widget(mem_paragraph, sections[0]);
 
-    let percent_ram_used = (used_memory as f64 / total_memory as f64) * 100.0;
+    // Some containers report no memory at all
+    let (percent_ram_used, label) = if total_memory > 0 {
+        let percent = (used_memory as f64 / total_memory as f64) * 100.0;
+        (percent, format!("{percent:.2} %"))
+    } else {
+        (0.0, "n/a".to_string())
+    };
     let mem_gauge = Gauge::default()
         .block(
             Block::default()
@@ -346,30 +719,37 @@ Free RAM: {free_memory} MB
         )
         .gauge_style(
             Style::default()
-                .fg(Color::Cyan)
-                .bg(Color::Black)
+                .fg(theme.accent)
+                .bg(theme.background)
         )
-        .percent(percent_ram_used as u16)
-        .label(format!("{percent_ram_used:.2} %"));
+        // Gauge panics above 100
+        .percent((percent_ram_used as u16).min(100))
+        .label(label);
     f.render_widget(mem_gauge, sections[1]);
 }
 
 fn draw_disks(f: &mut Frame, state: &State, area: &Rect) {
-    let disks_data: Vec<DiskData> = state.system.get_disk_data();
+    let theme = &state.theme;
+    // Disks scrolled past with the mouse wheel are left out, and so are the ones that don't fit.
+    // At least one disk is always shown
+    let disk_count = state.snapshot.disks.len();
+    let offset = state.disk_table_offset.min(disk_count);
+    let fitting = (area.width.saturating_sub(DISK_LABEL_WIDTH) / DISK_COLUMN_WIDTH).max(1) as usize;
+    let disks_data: &[DiskData] = &state.snapshot.disks[offset..(offset + fitting).min(disk_count)];
     let mut rows: Vec<Row> = Vec::new();
 
     // Push disk names into rows
     let mut disk_names: Vec<String> = vec!["Name".to_string()];
     disk_names.extend(
         disks_data.iter().map(|d| {
-            d.name.to_str().unwrap().to_string()
+            d.name.to_string_lossy().into_owned()
         }).collect::<Vec<String>>()
     );
     rows.push(
         Row::new(disk_names)
         .style(Style::default()
-            .fg(Color::White)
-            .bg(Color::Black)
+            .fg(theme.foreground)
+            .bg(theme.background)
     ));
 
     // Push disk kinds as strings
@@ -382,36 +762,36 @@ fn draw_disks(f: &mut Frame, state: &State, area: &Rect) {
     rows.push(
         Row::new(disk_kinds)
         .style(Style::default()
-            .fg(Color::White)
-            .bg(Color::Black)
+            .fg(theme.foreground)
+            .bg(theme.background)
     ));
 
     // Push disk available spaces
     let mut disk_mount_point: Vec<String> = vec!["Mount Point".to_string()];
     disk_mount_point.extend(
         disks_data.iter().map(|d| {
-            d.mount_point.to_str().unwrap().to_string()
+            d.mount_point.to_string_lossy().into_owned()
         }).collect::<Vec<String>>()
     );
     rows.push(
         Row::new(disk_mount_point)
         .style(Style::default()
-            .fg(Color::White)
-            .bg(Color::Black)    
+            .fg(theme.foreground)
+            .bg(theme.background)    
     ));
 
     // Push disk file system as strings
     let mut disk_file_systems: Vec<String> = vec!["File System".to_string()];
     disk_file_systems.extend(
         disks_data.iter().map(|d| {
-            d.file_system.to_str().unwrap().to_string()
+            d.file_system.to_string_lossy().into_owned()
         }).collect::<Vec<String>>()
     );
     rows.push(
         Row::new(disk_file_systems)
         .style(Style::default()
-            .fg(Color::White)
-            .bg(Color::Black)    
+            .fg(theme.foreground)
+            .bg(theme.background)    
     ));
 
 
@@ -426,8 +806,8 @@ fn draw_disks(f: &mut Frame, state: &State, area: &Rect) {
     rows.push(
         Row::new(disk_total_spaces)
         .style(Style::default()
-            .fg(Color::White)
-            .bg(Color::Black)    
+            .fg(theme.foreground)
+            .bg(theme.background)    
     ));
 
     // Push disk available spaces
@@ -441,30 +821,42 @@ fn draw_disks(f: &mut Frame, state: &State, area: &Rect) {
     rows.push(
         Row::new(disk_available_spaces)
         .style(Style::default()
-            .fg(Color::White)
-            .bg(Color::Black)    
+            .fg(theme.foreground)
+            .bg(theme.background)    
     ));
     
     // Depending on the number of disks installed, adjust the column size + 1 for descriptions
-    let mut table_constraints_vec: Vec<Constraint> = vec![Constraint::Min(21)];
+    let mut table_constraints_vec: Vec<Constraint> = vec![Constraint::Min(DISK_LABEL_WIDTH)];
     for _i in 0..disks_data.len() {
         // table_constraints_vec.push(Constraint::Percentage((100 / disks_data.len()) as u16))
-        table_constraints_vec.push(Constraint::Min(15));
+        table_constraints_vec.push(Constraint::Min(DISK_COLUMN_WIDTH));
     }
     let table_constraints_slice: &[Constraint] = table_constraints_vec.as_slice();
 
     // Define header
-    let mut header_titles: Vec<String> = vec!["".to_string()];
-    for i in 0..disks_data.len() {
-        header_titles.push(format!("Disk {}", i+1));
+    // The disk highlighted on the disk graph is highlighted here as well
+    // When some disks are hidden, tell which ones are shown
+    let shown = if disks_data.len() < disk_count {
+        format!("{}-{} of {} (scroll)", offset + 1, offset + disks_data.len(), disk_count)
+    } else {
+        String::new()
+    };
+    let mut header_titles: Vec<Cell> = vec![Cell::from(shown)];
+    for i in offset..offset + disks_data.len() {
+        let title = Cell::from(format!("Disk {}", i+1));
+        if i == state.disk_dataset.highlighted {
+            header_titles.push(title.style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)));
+        } else {
+            header_titles.push(title);
+        }
     }
     let header: Row = Row::new(header_titles);
 
     let disk_table = Table::new(rows, table_constraints_slice)
         .style(
             Style::default()
-            .fg(Color::LightCyan)
-            .bg(Color::Black)
+            .fg(theme.highlight)
+            .bg(theme.background)
         )
         .header(header)
         .column_spacing(0);
@@ -474,48 +866,102 @@ fn draw_disks(f: &mut Frame, state: &State, area: &Rect) {
 }
 
 
-fn draw_cpu_graph(f: &mut Frame, state: &mut State, area: &Rect, elapsed_ms: f64) {
-    let (data, _) = state.cpu_dataset.get_cpu_usage_as_slice();
-    let cpu_dataset = Dataset::default()
-            .name("CPU Usage")
-            .marker(Marker::HalfBlock)
-            .graph_type(GraphType::Line)
-            .style(
-                Style::default()
-                    .fg(Color::LightCyan)
-                )
-            .data(
-                data
-            );
+fn draw_network(f: &mut Frame, state: &State, area: &Rect) {
+    let theme = &state.theme;
+    let header = Row::new(vec!["Interface", "RX/s", "TX/s", "RX Total", "TX Total", "Packets (RX/TX)", "Errors (RX/TX)"]);
 
-    let mut dataset_vec = Vec::new();
-    dataset_vec.push(cpu_dataset);
+    let rows: Vec<Row> = state.network_dataset.interfaces.iter().map(|i| {
+        let n: &NetworkData = &i.data;
+        Row::new(vec![
+            n.name.clone(),
+            format_bytes(i.current_rx_rate()),
+            format_bytes(i.current_tx_rate()),
+            format_bytes(n.total_received as f64),
+            format_bytes(n.total_transmitted as f64),
+            format!("{}/{}", n.total_packets_received, n.total_packets_transmitted),
+            format!("{}/{}", n.total_errors_on_received, n.total_errors_on_transmitted)
+        ])
+        .style(Style::default()
+            .fg(theme.foreground)
+            .bg(theme.background)
+        )
+    }).collect();
 
-    let right_bound = elapsed_ms;
+    let network_table = Table::new(
+        rows,
+        [
+            Constraint::Min(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(16),
+            Constraint::Length(15)
+        ]
+    )
+        .style(
+            Style::default()
+            .fg(theme.highlight)
+            .bg(theme.background)
+        )
+        .header(header)
+        .column_spacing(1);
+
+    f.render_widget(network_table, *area);
+}
+
+fn draw_cpu_graph(f: &mut Frame, state: &State, area: &Rect, elapsed_ms: f64) {
+    let theme = &state.theme;
 
-    // This is hardcoded and should not be.
-    // Same value is used in state.rs CpuDataset update_cpu_usage()
-    let left_bound: f64 = if elapsed_ms <= 25000.0 { 0.0 } else { elapsed_ms - 25000.0 };
+    // Usage takes the whole pane unless the frequency history is shown below it
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            if state.cpu_show_frequency {
+                [
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(40)
+                ]
+            } else {
+                [
+                    Constraint::Percentage(100),
+                    Constraint::Percentage(0)
+                ]
+            }
+        )
+        .split(*area);
 
-    // Create graph labels as &[&str] based on the current right_bound f64 value.
-    // This is messy and surely there's a better way I do not know of
-    let mut graph_labels: Vec<f64> = Vec::new();
+    let cpu_dataset = &state.cpu_dataset;
 
-    if elapsed_ms <= 25000.0 {
-        graph_labels.push(0.0);
+    let dataset_vec: Vec<Dataset> = if state.cpu_per_core {
+        cpu_dataset.per_core_usage.iter().enumerate().map(|(i, core)| {
+            Dataset::default()
+                .name(format!("CPU {i}"))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(
+                    Style::default()
+                        .fg(theme.series_color(i))
+                )
+                .data(core.as_slice())
+        }).collect()
     } else {
-        graph_labels.push(elapsed_ms - 25000.0)
+        vec![
+            Dataset::default()
+                .name("CPU Usage")
+                .marker(Marker::HalfBlock)
+                .graph_type(GraphType::Line)
+                .style(
+                    Style::default()
+                        .fg(theme.highlight)
+                    )
+                .data(
+                    cpu_dataset.cpu_usage.as_slice()
+                )
+        ]
     };
-    graph_labels.push(right_bound/2.0);
-    graph_labels.push(right_bound);
-    
-    
-    let graph_labels = graph_labels.into_iter().map(|i| {
-        i.to_string()
-    }).collect::<Vec<String>>();
-    let graph_labels: Vec<&str> = graph_labels.iter().map(|i| {
-        i.as_ref()
-    }).collect::<Vec<&str>>();
+
+    let (x_bounds, x_labels) = time_axis(elapsed_ms, state.settings.history_ms());
 
     let cpu_chart = Chart::new(dataset_vec)
         .block(Block::default())
@@ -524,38 +970,968 @@ fn draw_cpu_graph(f: &mut Frame, state: &mut State, area: &Rect, elapsed_ms: f64
                 .title(Span::styled(
                     "Time (ms)",
                     Style::default()
-                        .bg(Color::Black)
-                        .fg(Color::White)
+                        .bg(theme.background)
+                        .fg(theme.foreground)
                     )
                 )
                 .style(
                     Style::default()
-                        .bg(Color::Black)
-                        .fg(Color::White)
+                        .bg(theme.background)
+                        .fg(theme.foreground)
                 )
-                .bounds([left_bound, right_bound])
-                .labels(graph_labels.as_slice().iter().cloned().map(Span::from).collect())
+                .bounds(x_bounds)
+                .labels(x_labels)
         )
         .y_axis(
             Axis::default()
                 .title(Span::styled(
                     "Usage (%)",
                     Style::default()
-                        .bg(Color::Black)
-                        .fg(Color::White)
+                        .bg(theme.background)
+                        .fg(theme.foreground)
                     )
                 )
                 .style(
                     Style::default()
-                        .bg(Color::Black)
-                        .fg(Color::White)
+                        .bg(theme.background)
+                        .fg(theme.foreground)
                 )
                 .bounds([0.0, 100.0])
                 .labels(["0.0", "25.0", "50.0", "75.0", "100.0"].iter().cloned().map(Span::from).collect())
         )
-        .fg(Color::White)
-        .bg(Color::Black)
+        .fg(theme.foreground)
+        .bg(theme.background)
+        .legend_position(Some(LegendPosition::TopRight))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(cpu_chart, sections[0]);
+
+    if state.cpu_show_frequency {
+        draw_frequency_chart(f, state, &sections[1], elapsed_ms);
+    }
+}
+
+fn draw_frequency_chart(f: &mut Frame, state: &State, area: &Rect, elapsed_ms: f64) {
+    let theme = &state.theme;
+    let cpu_dataset = &state.cpu_dataset;
+
+    // Values are in MHz, leave some room above the highest frequency seen so far
+    let y_max = cpu_dataset.max_frequency.unwrap_or(1000).max(1) as f64 * 1.2;
+
+    let frequency_dataset = Dataset::default()
+        .name("Avg Frequency")
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(
+            Style::default()
+                .fg(theme.accent)
+        )
+        .data(cpu_dataset.frequency.as_slice());
+
+    let (x_bounds, _) = time_axis(elapsed_ms, state.settings.history_ms());
+    let y_labels: Vec<Span> = [0.0, 0.5, 1.0].iter()
+        .map(|fraction| Span::from(format!("{:.2}", y_max * fraction / 1000.0)))
+        .collect();
+
+    let frequency_chart = Chart::new(vec![frequency_dataset])
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_type(BorderType::Plain)
+        )
+        .x_axis(
+            Axis::default()
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds(x_bounds)
+        )
+        .y_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "Frequency (GHz)",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds([0.0, y_max])
+                .labels(y_labels)
+        )
+        .fg(theme.foreground)
+        .bg(theme.background)
         .legend_position(Some(LegendPosition::TopRight));
-    f.render_widget(cpu_chart, *area);
+    f.render_widget(frequency_chart, *area);
+}
+
+fn draw_memory_graph(f: &mut Frame, state: &State, area: &Rect, elapsed_ms: f64) {
+    let theme = &state.theme;
+
+    // Upper bound is whichever is bigger between RAM and swap, so both fit on the same axis
+    let dataset = &state.memory_dataset;
+    let y_max = dataset.total_memory.max(dataset.total_swap).max(1) as f64;
+
+    let used_memory_dataset = Dataset::default()
+        .name("Used RAM")
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.critical))
+        .data(dataset.used_memory.as_slice());
+    let free_memory_dataset = Dataset::default()
+        .name("Free RAM")
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.ok))
+        .data(dataset.free_memory.as_slice());
+    let used_swap_dataset = Dataset::default()
+        .name("Used Swap")
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.warning))
+        .data(dataset.used_swap.as_slice());
+
+    let dataset_vec = vec![used_memory_dataset, free_memory_dataset, used_swap_dataset];
+
+    let (x_bounds, x_labels) = time_axis(elapsed_ms, state.settings.history_ms());
+    let y_labels: Vec<Span> = [0.0, 0.25, 0.5, 0.75, 1.0].iter()
+        .map(|fraction| Span::from(format_megabytes(y_max * fraction)))
+        .collect();
+
+    let memory_chart = Chart::new(dataset_vec)
+        .block(Block::default())
+        .x_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "Time (ms)",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds(x_bounds)
+                .labels(x_labels)
+        )
+        .y_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "Memory",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds([0.0, y_max])
+                .labels(y_labels)
+        )
+        .fg(theme.foreground)
+        .bg(theme.background)
+        .legend_position(Some(LegendPosition::TopRight))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(memory_chart, *area);
+}
+
+fn draw_disk_graph(f: &mut Frame, state: &State, area: &Rect, elapsed_ms: f64) {
+    let theme = &state.theme;
+
+    // Throughput on top, used capacity below:
+    //  -------------------------
+    // |   Read/write (MB/s)    |
+    // |------------------------|
+    // |   Used capacity (%)    |
+    //  -------------------------
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(50),
+                Constraint::Percentage(50)
+            ]
+        )
+        .split(*area);
+
+    let highlighted = state.disk_dataset.highlighted;
+    let disks = &state.disk_dataset.disks;
+
+    // Every disk gets drawn, but only the highlighted one stands out
+    let line_style = |i: usize, color: Color| {
+        if i == highlighted {
+            Style::default().fg(color).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.muted)
+        }
+    };
+
+    let mut throughput_datasets: Vec<Dataset> = Vec::new();
+    let mut capacity_datasets: Vec<Dataset> = Vec::new();
+    let mut max_rate: f64 = 1.0;
+    for (i, disk) in disks.iter().enumerate() {
+        let read_data = disk.read_rate.as_slice();
+        let write_data = disk.write_rate.as_slice();
+        max_rate = [disk.read_rate.max(), disk.write_rate.max()].into_iter().flatten().fold(max_rate, f64::max);
+
+        throughput_datasets.push(
+            Dataset::default()
+                .name(format!("{} read", disk.name))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(line_style(i, theme.ok))
+                .data(read_data)
+        );
+        throughput_datasets.push(
+            Dataset::default()
+                .name(format!("{} write", disk.name))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(line_style(i, theme.critical))
+                .data(write_data)
+        );
+        capacity_datasets.push(
+            Dataset::default()
+                .name(format!("{} ({})", disk.name, disk.mount_point))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(line_style(i, theme.highlight))
+                .data(disk.used_space.as_slice())
+        );
+    }
+
+    let title = match disks.get(highlighted) {
+        Some(disk) => format!("Highlighted: {} ({})", disk.name, disk.mount_point),
+        None => "No disks found".to_string()
+    };
+
+    let (x_bounds, x_labels) = time_axis(elapsed_ms, state.settings.history_ms());
+    let rate_labels: Vec<Span> = [0.0, 0.5, 1.0].iter()
+        .map(|fraction| Span::from(format!("{:.1}", max_rate * fraction)))
+        .collect();
+
+    let throughput_chart = Chart::new(throughput_datasets)
+        .block(
+            Block::default()
+                .title(title)
+                .title_alignment(Alignment::Center)
+        )
+        .x_axis(
+            Axis::default()
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds(x_bounds)
+        )
+        .y_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "I/O (MB/s)",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds([0.0, max_rate])
+                .labels(rate_labels)
+        )
+        .fg(theme.foreground)
+        .bg(theme.background)
+        .legend_position(Some(LegendPosition::TopRight))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(throughput_chart, sections[0]);
+
+    let capacity_chart = Chart::new(capacity_datasets)
+        .block(Block::default())
+        .x_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "Time (ms)",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds(x_bounds)
+                .labels(x_labels)
+        )
+        .y_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "Used (%)",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds([0.0, 100.0])
+                .labels(["0.0", "50.0", "100.0"].iter().cloned().map(Span::from).collect())
+        )
+        .fg(theme.foreground)
+        .bg(theme.background)
+        .legend_position(Some(LegendPosition::BottomRight))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(capacity_chart, sections[1]);
+}
 
-}
\ No newline at end of file
+fn draw_network_graph(f: &mut Frame, state: &State, area: &Rect, elapsed_ms: f64) {
+    let theme = &state.theme;
+
+    let mut dataset_vec: Vec<Dataset> = Vec::new();
+    let mut max_rate: f64 = 1000.0;
+    for (i, interface) in state.network_dataset.interfaces.iter().enumerate() {
+        // Each interface gets a pair of colors, one for rx and one for tx
+        let (rx_color, tx_color) = (theme.series_color(2 * i), theme.series_color(2 * i + 1));
+        let rx_data = interface.rx_rate.as_slice();
+        let tx_data = interface.tx_rate.as_slice();
+        max_rate = [interface.rx_rate.max(), interface.tx_rate.max()].into_iter().flatten().fold(max_rate, f64::max);
+
+        dataset_vec.push(
+            Dataset::default()
+                .name(format!("{} rx", interface.data.name))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(rx_color))
+                .data(rx_data)
+        );
+        dataset_vec.push(
+            Dataset::default()
+                .name(format!("{} tx", interface.data.name))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(tx_color))
+                .data(tx_data)
+        );
+    }
+
+    let (x_bounds, x_labels) = time_axis(elapsed_ms, state.settings.history_ms());
+    let y_labels: Vec<Span> = [0.0, 0.25, 0.5, 0.75, 1.0].iter()
+        .map(|fraction| Span::from(format!("{}/s", format_bytes(max_rate * fraction))))
+        .collect();
+
+    let network_chart = Chart::new(dataset_vec)
+        .block(Block::default())
+        .x_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "Time (ms)",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds(x_bounds)
+                .labels(x_labels)
+        )
+        .y_axis(
+            Axis::default()
+                .title(Span::styled(
+                    "Throughput",
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                    )
+                )
+                .style(
+                    Style::default()
+                        .bg(theme.background)
+                        .fg(theme.foreground)
+                )
+                .bounds([0.0, max_rate])
+                .labels(y_labels)
+        )
+        .fg(theme.foreground)
+        .bg(theme.background)
+        .legend_position(Some(LegendPosition::TopRight))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    f.render_widget(network_chart, *area);
+}
+
+fn draw_process_table(f: &mut Frame, state: &mut State, area: &Rect) {
+    let theme = &state.theme;
+
+    // Sort/filter status line on top, table below
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Min(1)
+            ]
+        )
+        .split(*area);
+
+    let view = &mut state.process_view;
+
+    let order = if view.sort_descending { "desc" } else { "asc" };
+    let cursor = if view.filtering { "_" } else { "" };
+    let status = format!(
+        "{} processes | Sort: {} ({order}) | Filter: {}{cursor}",
+        view.rows.len(),
+        view.sort_column.title(),
+        view.filter
+    );
+    let status_style = if view.filtering {
+        Style::default().fg(theme.background).bg(theme.highlight)
+    } else {
+        Style::default().fg(theme.foreground).bg(theme.background)
+    };
+    f.render_widget(Paragraph::new(status).style(status_style), sections[0]);
+
+    // Sorted column gets an arrow next to its title
+    let header_titles: Vec<Cell> = ProcessColumn::ALL.iter().map(|column| {
+        if *column == view.sort_column {
+            let arrow = if view.sort_descending { "▼" } else { "▲" };
+            Cell::from(format!("{}{arrow}", column.title()))
+                .style(Style::default().add_modifier(Modifier::BOLD))
+        } else {
+            Cell::from(column.title())
+        }
+    }).collect();
+    let header = Row::new(header_titles)
+        .style(Style::default().fg(theme.highlight).bg(theme.background));
+
+    let rows: Vec<Row> = view.rows.iter().map(|p| {
+        Row::new(vec![
+            p.pid.to_string(),
+            p.name.clone(),
+            p.user.clone(),
+            format!("{:.1}", p.cpu_usage),
+            format_bytes(p.memory as f64),
+            format!("{}/{}", format_bytes(p.total_read_bytes as f64), format_bytes(p.total_written_bytes as f64)),
+            p.status.clone(),
+            p.command.clone()
+        ])
+        .style(Style::default()
+            .fg(theme.foreground)
+            .bg(theme.background)
+        )
+    }).collect();
+
+    let process_table = Table::new(
+        rows,
+        [
+            Constraint::Length(7),
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(9),
+            Constraint::Length(19),
+            Constraint::Length(9),
+            Constraint::Min(10)
+        ]
+    )
+        .style(
+            Style::default()
+            .fg(theme.foreground)
+            .bg(theme.background)
+        )
+        .header(header)
+        .highlight_style(
+            Style::default()
+                .fg(theme.background)
+                .bg(theme.highlight)
+        )
+        .column_spacing(1);
+
+    f.render_stateful_widget(process_table, sections[1], &mut view.table_state);
+}
+
+fn draw_alert_log(f: &mut Frame, state: &State, area: &Rect) {
+    let theme = &state.theme;
+    let alerts = &state.alerts;
+
+    // Rules currently firing on top, log below
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Min(1)
+            ]
+        )
+        .split(*area);
+
+    let firing: Vec<&str> = alerts.firing().map(|rule| rule.name.as_str()).collect();
+    let (summary, summary_style) = if alerts.rule_count() == 0 {
+        (
+            "No alert rules, add [[alerts]] tables to the config file".to_string(),
+            Style::default().fg(theme.muted).bg(theme.background)
+        )
+    } else if firing.is_empty() {
+        (
+            format!("{} rules | None firing", alerts.rule_count()),
+            Style::default().fg(theme.ok).bg(theme.background)
+        )
+    } else {
+        (
+            format!("{} rules | Firing: {}", alerts.rule_count(), firing.join(", ")),
+            Style::default().fg(theme.critical).bg(theme.background).add_modifier(Modifier::BOLD)
+        )
+    };
+    f.render_widget(Paragraph::new(summary).style(summary_style), sections[0]);
+
+    let header = Row::new(vec!["Time", "State", "Value", "Rule"])
+        .style(Style::default().fg(theme.highlight).bg(theme.background));
+
+    // Newest first
+    let rows: Vec<Row> = alerts.log.iter().rev().skip(alerts.log_offset).map(|event| {
+        let (label, color) = if event.firing { ("FIRING", theme.critical) } else { ("resolved", theme.ok) };
+        Row::new(vec![
+            Cell::from(replay::format_utc_time(event.timestamp_ms)),
+            Cell::from(label).style(Style::default().fg(color)),
+            Cell::from(event.value.clone()),
+            Cell::from(event.rule.clone())
+        ])
+    }).collect();
+
+    let alert_table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            // Rule names get whatever width is left
+            Constraint::Length(sections[1].width.saturating_sub(12 + 8 + 12 + 3))
+        ]
+    )
+        .style(
+            Style::default()
+            .fg(theme.foreground)
+            .bg(theme.background)
+        )
+        .header(header)
+        .column_spacing(1);
+
+    f.render_widget(alert_table, sections[1]);
+}
+
+fn draw_status(f: &mut Frame, state: &State, area: &Rect) {
+    let theme = &state.theme;
+    let message = state.status_message.as_deref().unwrap_or("");
+    let status = Paragraph::new(message)
+        .style(
+            Style::default()
+                .fg(theme.warning)
+                .bg(theme.background)
+        );
+    f.render_widget(status, *area);
+
+    // Playback position on the right while replaying, or the jump prompt while typing one
+    let Some(replay) = &state.replay else {
+        return;
+    };
+    let (text, style) = match &replay.jump_input {
+        Some(input) => (
+            format!(" Jump to (s, m:ss, +h:mm:ss or hh:mm:ss UTC): {input}_ "),
+            Style::default().fg(theme.background).bg(theme.highlight)
+        ),
+        None => {
+            let offset = |timestamp_ms: u64| replay::format_offset(
+                Duration::from_millis(timestamp_ms - replay.start_ms())
+            );
+            (
+                format!(
+                    " {} {}x  {} / {}  {} ",
+                    if replay.playing { "Playing" } else { "Paused" },
+                    replay.speed(),
+                    offset(replay.position_ms()),
+                    offset(replay.end_ms()),
+                    replay::format_utc_time(replay.position_ms())
+                ),
+                Style::default().fg(theme.accent).bg(theme.background)
+            )
+        }
+    };
+    let width = (text.chars().count() as u16).min(area.width);
+    let replay_area = Rect::new(area.right() - width, area.y, width, 1);
+    f.render_widget(Clear, replay_area);
+    f.render_widget(Paragraph::new(text).style(style), replay_area);
+}
+
+fn draw_signal_dialog(f: &mut Frame, state: &State, area: &Rect) {
+    let theme = &state.theme;
+    let Some(dialog) = &state.signal_dialog else {
+        return;
+    };
+
+    // Small popup in the middle of the graph pane
+    let width = area.width.min(44);
+    let height = area.height.min(12);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height
+    );
+
+    let items: Vec<ListItem> = dialog.signals.iter()
+        .map(|signal| ListItem::new(sys_poller::signal_name(*signal)))
+        .collect();
+    let signal_list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!("Send signal to {} ({})", dialog.pid, dialog.name))
+                .title(Title::from("Enter: send | Esc: cancel").position(Position::Bottom))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(
+                    Style::default()
+                        .fg(theme.critical)
+                        .bg(theme.background)
+                )
+        )
+        .style(
+            Style::default()
+                .fg(theme.foreground)
+                .bg(theme.background)
+        )
+        .highlight_style(
+            Style::default()
+                .fg(theme.background)
+                .bg(theme.critical)
+        );
+
+    let mut list_state = ListState::default().with_selected(Some(dialog.selected));
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(signal_list, popup, &mut list_state);
+}
+
+// Every binding in as many columns as fit, scrolled together when they don't fit either
+fn draw_key_list(f: &mut Frame, state: &mut State, area: &Rect) {
+    let theme = &state.theme;
+    let lines = key_list(state);
+
+    // Borders and padding take 4 columns and 2 rows
+    let columns = (area.width.saturating_sub(4) / KEY_LIST_COLUMN_WIDTH).max(1) as usize;
+    let rows = lines.len().div_ceil(columns);
+    let width = area.width.min(columns as u16 * KEY_LIST_COLUMN_WIDTH + 4);
+    let height = area.height.min(rows as u16 + 2);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height
+    );
+    let block = Block::default()
+        .title("Keys")
+        .title(Title::from("Up/Down: scroll | any other key: close").position(Position::Bottom))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(
+            Style::default()
+                .fg(theme.accent)
+                .bg(theme.background)
+        )
+        .style(
+            Style::default()
+                .fg(theme.foreground)
+                .bg(theme.background)
+        )
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(popup);
+
+    let offset = state.help_offset.unwrap_or(0).min(rows.saturating_sub(inner.height as usize));
+    state.help_offset = Some(offset);
+
+    f.render_widget(Clear, popup);
+    f.render_widget(block, popup);
+    let column_areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+        .split(inner);
+    for (column, chunk) in lines.chunks(rows.max(1)).enumerate() {
+        let paragraph = Paragraph::new(chunk.to_vec()).scroll((offset as u16, 0));
+        f.render_widget(paragraph, column_areas[column]);
+    }
+}
+
+// X axis bounds and labels for the history window ending at elapsed_ms
+fn time_axis(elapsed_ms: f64, history_ms: f64) -> ([f64; 2], Vec<Span<'static>>) {
+    let right_bound = elapsed_ms;
+    let left_bound: f64 = if elapsed_ms <= history_ms { 0.0 } else { elapsed_ms - history_ms };
+
+    let labels = [left_bound, (left_bound + right_bound) / 2.0, right_bound]
+        .iter()
+        .map(|i| Span::from(format!("{i:.0}")))
+        .collect();
+
+    ([left_bound, right_bound], labels)
+}
+
+// Frequency in MHz as GHz, or n/a when nothing was reported yet
+fn format_frequency(mhz: Option<u64>) -> String {
+    match mhz {
+        Some(mhz) => format!("{:.2} GHz", mhz as f64 / 1000.0),
+        None => "n/a".to_string()
+    }
+}
+
+// Values come in as MB, switch to GB once they get big enough
+fn format_megabytes(mb: f64) -> String {
+    if mb >= 1000.0 {
+        format!("{:.1} GB", mb / 1000.0)
+    } else {
+        format!("{mb:.0} MB")
+    }
+}
+
+// Human readable size for a byte count
+fn format_bytes(bytes: f64) -> String {
+    if bytes >= 1_000_000_000.0 {
+        format!("{:.1} GB", bytes / 1_000_000_000.0)
+    } else if bytes >= 1_000_000.0 {
+        format!("{:.1} MB", bytes / 1_000_000.0)
+    } else if bytes >= 1_000.0 {
+        format!("{:.1} KB", bytes / 1_000.0)
+    } else {
+        format!("{bytes:.0} B")
+    }
+}
+
+// Golden snapshot tests. Every case renders create_ui into a TestBackend from fixed metric data and
+// compares the text on screen with src/snapshots/<case>.txt. After an intended layout change,
+// rerun with UPDATE_SNAPSHOTS=1 to rewrite the stored files and review the diff
+#[cfg(test)]
+mod tests {
+    use std::{ffi::OsString, fs, path::PathBuf, time::Duration};
+    use ratatui::{backend::TestBackend, Terminal};
+    use sysinfo::DiskKind;
+    use super::*;
+    use crate::{
+        alerts::{Alerts, Comparison, Metric},
+        config::Config,
+        metrics::{fixtures::{alert_rule, disk, network, process}, scripted::ScriptedSource},
+        sys_poller::Snapshot
+    };
+
+    const FRAME_COUNT: u64 = 12;
+    const GB: u64 = 1_000_000_000;
+    const MB: u64 = 1_000_000;
+
+    fn frame(i: u64) -> Snapshot {
+        let load = (i * 7 % 10) as f64;
+        Snapshot {
+            timestamp_ms: 1_700_000_000_000 + i * 1000,
+            cpu_brand: Some("Test CPU @ 3.00GHz".to_string()),
+            core_count: Some(4),
+            avg_cpu_usage: 20.0 + load * 5.0,
+            per_core_usage: (0..4).map(|core| 10.0 + core as f64 * 20.0 + load).collect(),
+            avg_cpu_frequency: Some(2400),
+            per_core_frequency: (0..4).map(|core| 2000 + core * 200 + i * 10).collect(),
+            memory: (6000 - i * 100, 2000 + i * 100, 8000),
+            swap: (1500, 500, 2000),
+            disks: vec![
+                disk("nvme0n1p2", "/", 512 * GB, 200 * GB, i * 4_000_000, i * 1_000_000),
+                disk("sda1", "/home", 1000 * GB, 750 * GB, i * 4_000_000, i * 1_000_000)
+            ],
+            networks: vec![network("eth0", i * 250_000, i * 50_000), network("lo", i * 1000, i * 1000)],
+            processes: vec![
+                process(1, "init", 0.1, 12 * MB),
+                process(812, "postgres", 12.5, 420 * MB),
+                process(1337, "cargo", 87.0 - load as f32, 1800 * MB),
+                process(2048, "bash", 0.0, 5 * MB)
+            ]
+        }
+    }
+
+    // The sampler only delivers the first frame, the long interval keeps it from sending more.
+    // The rest are applied directly so the datasets are the same on every run
+    fn scripted_state() -> (State, f64) {
+        let mut config = Config::default();
+        config.settings.sample_interval = Duration::from_secs(3600);
+        let mut state = State::new(ScriptedSource::new(vec![frame(0)]), &config);
+        let mut elapsed_ms = state.wait_for_messages(1);
+        for i in 1..FRAME_COUNT {
+            state.apply_snapshot(frame(i));
+            elapsed_ms = state.elapsed_ms;
+        }
+        (state, elapsed_ms)
+    }
+
+    fn render(width: u16, height: u16, setup: impl FnOnce(&mut State)) -> String {
+        let (mut state, elapsed_ms) = scripted_state();
+        setup(&mut state);
+
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| create_ui(f, &mut state, elapsed_ms)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            let line: String = (0..buffer.area.width).map(|x| buffer.get(x, y).symbol()).collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    fn assert_snapshot(name: &str, rendered: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/snapshots").join(format!("{name}.txt"));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, rendered).unwrap();
+            return;
+        }
+
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("missing snapshot {}: {e}, run with UPDATE_SNAPSHOTS=1 to create it", path.display()));
+        if expected != rendered {
+            let first_difference = expected.lines().zip(rendered.lines())
+                .position(|(expected, rendered)| expected != rendered)
+                .unwrap_or_else(|| expected.lines().count().min(rendered.lines().count()));
+            panic!(
+                "snapshot {name} changed at line {}\n--- expected\n{expected}\n--- rendered\n{rendered}",
+                first_difference + 1
+            );
+        }
+    }
+
+    #[test]
+    fn cpu_graph_wide() {
+        assert_snapshot("cpu_graph_wide", &render(160, 50, |_| {}));
+    }
+
+    #[test]
+    fn cpu_graph_per_core_with_frequency() {
+        assert_snapshot("cpu_graph_per_core_with_frequency", &render(160, 50, |state| {
+            state.toggle_cpu_per_core();
+            state.toggle_cpu_frequency();
+        }));
+    }
+
+    #[test]
+    fn memory_graph_wide() {
+        assert_snapshot("memory_graph_wide", &render(160, 50, |state| state.set_graph(Graph::MEMORY)));
+    }
+
+    #[test]
+    fn disk_graph_wide() {
+        assert_snapshot("disk_graph_wide", &render(160, 50, |state| state.set_graph(Graph::DISK)));
+    }
+
+    #[test]
+    fn network_graph_wide() {
+        assert_snapshot("network_graph_wide", &render(160, 50, |state| state.set_graph(Graph::NETWORK)));
+    }
+
+    #[test]
+    fn process_view_wide() {
+        assert_snapshot("process_view_wide", &render(160, 50, |state| state.set_graph(Graph::PROCESS)));
+    }
+
+    #[test]
+    fn stacked_layout() {
+        assert_snapshot("stacked_layout", &render(90, 40, |_| {}));
+    }
+
+    // Every info block shows its text and gauge at ordinary terminal sizes
+    #[test]
+    fn info_panels_show_their_data() {
+        for (width, height) in [(120, 40), (160, 50), (90, 40)] {
+            let rendered = render(width, height, |_| {});
+            for expected in [
+                "Test CPU @ 3.00GHz", "Cores: 4", "Usage (%)", "55.00 %",
+                "Total RAM: 8000 MB", "Free RAM: 4900 MB", "RAM usage (MB)", "38.75 %",
+                "Mount Point", "Available Space (GB)",
+                "eth0", "lo "
+            ] {
+                assert!(rendered.contains(expected), "{expected:?} missing at {width}x{height}:\n{rendered}");
+            }
+        }
+        assert_snapshot("info_panels_120x40", &render(120, 40, |_| {}));
+    }
+
+    #[test]
+    fn collapsed_upper_section() {
+        assert_snapshot("collapsed_upper_section", &render(120, 24, |state| state.set_graph(Graph::DISK)));
+    }
+
+    #[test]
+    fn disk_table_scrolled() {
+        assert_snapshot("disk_table_scrolled", &render(60, 20, |state| state.scroll_disk_table(1)));
+    }
+
+    #[test]
+    fn alert_log() {
+        assert_snapshot("alert_log", &render(160, 50, |state| {
+            let rules = vec![
+                alert_rule(Metric::Cpu, Comparison::Above(50.0), 0),
+                alert_rule(Metric::Memory, Comparison::Above(30.0), 2),
+                alert_rule(Metric::DiskAvailable("/home".to_string()), Comparison::Below(800.0), 0),
+                alert_rule(Metric::ProcessCount("nginx".to_string()), Comparison::Below(1.0), 0)
+            ];
+            state.alerts = Alerts::new(rules, false);
+            for i in 0..FRAME_COUNT {
+                state.alerts.evaluate(&frame(i));
+            }
+            state.set_graph(Graph::ALERTS);
+        }));
+    }
+
+    #[test]
+    fn firing_alerts_blink() {
+        let (mut state, elapsed_ms) = scripted_state();
+        state.alerts = Alerts::new(vec![alert_rule(Metric::Cpu, Comparison::Above(10.0), 0)], false);
+        state.alerts.evaluate(&frame(FRAME_COUNT));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let mut border_styles = Vec::new();
+        for _ in 0..2 {
+            terminal.draw(|f| create_ui(f, &mut state, elapsed_ms)).unwrap();
+            let cpu_area = state.areas["cpu_info"];
+            border_styles.push(terminal.backend().buffer().get(cpu_area.x, cpu_area.y).style());
+            state.frame_count += ALERT_BLINK_FRAMES - 1;
+        }
+        assert_ne!(border_styles[0], border_styles[1]);
+    }
+
+    #[test]
+    fn key_list_overlay() {
+        assert_snapshot("key_list_overlay", &render(120, 40, State::toggle_help));
+        // Too narrow for every column, scrolling stops at the last row
+        let narrow = render(60, 30, |state| {
+            state.toggle_help();
+            state.scroll_help(isize::MAX);
+        });
+        assert!(narrow.contains("Send Signal"), "{narrow}");
+    }
+
+    #[test]
+    fn too_small() {
+        assert_snapshot("too_small", &render(40, 10, |_| {}));
+    }
+}